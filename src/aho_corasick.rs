@@ -0,0 +1,189 @@
+use std::collections::VecDeque;
+
+const ROOT: usize = 0;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Match {
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+struct State {
+    transitions: [u32; 256],
+    outputs: Vec<usize>,
+}
+
+impl State {
+    fn new() -> State {
+        State {
+            transitions: [ROOT as u32; 256],
+            outputs: Vec::new(),
+        }
+    }
+}
+
+pub struct AhoCorasick {
+    states: Vec<State>,
+    pattern_lens: Vec<usize>,
+}
+
+impl AhoCorasick {
+    pub fn new<I, P>(patterns: I) -> AhoCorasick
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        let mut states = vec![State::new()];
+        let mut pattern_lens = Vec::new();
+        // While building the trie, a zero transition out of a non-root state means "missing".
+        for (pattern_idx, pattern) in patterns.into_iter().enumerate() {
+            let pattern = pattern.as_ref();
+            let mut curr = ROOT;
+            for &byte in pattern {
+                let next = states[curr].transitions[byte as usize] as usize;
+                curr = if next == ROOT {
+                    states.push(State::new());
+                    let new = states.len() - 1;
+                    states[curr].transitions[byte as usize] = new as u32;
+                    new
+                } else {
+                    next
+                };
+            }
+            states[curr].outputs.push(pattern_idx);
+            pattern_lens.push(pattern.len());
+        }
+
+        let mut fail = vec![ROOT; states.len()];
+        let mut queue: VecDeque<usize> = states[ROOT]
+            .transitions
+            .iter()
+            .map(|&next| next as usize)
+            .filter(|&next| next != ROOT)
+            .collect();
+        while let Some(curr) = queue.pop_front() {
+            let inherited = states[fail[curr]].outputs.clone();
+            states[curr].outputs.extend(inherited);
+            for byte in 0..256 {
+                let next = states[curr].transitions[byte] as usize;
+                let fallback = states[fail[curr]].transitions[byte];
+                if next == ROOT {
+                    states[curr].transitions[byte] = fallback;
+                } else {
+                    fail[next] = fallback as usize;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        AhoCorasick {
+            states,
+            pattern_lens,
+        }
+    }
+
+    pub fn pattern_count(&self) -> usize {
+        self.pattern_lens.len()
+    }
+
+    pub fn find_all(&self, haystack: impl AsRef<[u8]>) -> Vec<Match> {
+        let mut matches = Vec::new();
+        let mut push_outputs = |state: usize, end: usize| {
+            for &pattern in &self.states[state].outputs {
+                matches.push(Match {
+                    pattern,
+                    start: end - self.pattern_lens[pattern],
+                    end,
+                });
+            }
+        };
+
+        let mut curr = ROOT;
+        push_outputs(curr, 0);
+        for (i, &byte) in haystack.as_ref().iter().enumerate() {
+            curr = self.states[curr].transitions[byte as usize] as usize;
+            push_outputs(curr, i + 1);
+        }
+        matches
+    }
+
+    pub fn is_match(&self, haystack: impl AsRef<[u8]>) -> bool {
+        let mut curr = ROOT;
+        if !self.states[curr].outputs.is_empty() {
+            return true;
+        }
+        for &byte in haystack.as_ref() {
+            curr = self.states[curr].transitions[byte as usize] as usize;
+            if !self.states[curr].outputs.is_empty() {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::substring_search::kmp_search;
+
+    #[test]
+    fn it_works() {
+        let ac = AhoCorasick::new(["he", "she", "his", "hers"]);
+        let mut matches = ac.find_all("ushers");
+        matches.sort_by_key(|m| (m.start, m.pattern));
+        assert_eq!(
+            matches,
+            vec![
+                Match {
+                    pattern: 1,
+                    start: 1,
+                    end: 4
+                },
+                Match {
+                    pattern: 0,
+                    start: 2,
+                    end: 4
+                },
+                Match {
+                    pattern: 3,
+                    start: 2,
+                    end: 6
+                },
+            ]
+        );
+        assert!(ac.is_match("this"));
+        assert!(!ac.is_match("hx sx"));
+        assert_eq!(ac.pattern_count(), 4);
+    }
+
+    #[test]
+    fn matches_single_pattern_search() {
+        let patterns = ["ERROR", "WARN", "RR", "R", "timeout", "out of memory"];
+        let haystack = "INFO ok\nWARN slow\nERROR timeout\nERROR out of memory\nWARNWARN";
+        let ac = AhoCorasick::new(patterns);
+        let matches = ac.find_all(haystack);
+        for (idx, pattern) in patterns.iter().enumerate() {
+            let mut starts: Vec<usize> = matches
+                .iter()
+                .filter(|m| m.pattern == idx)
+                .map(|m| m.start)
+                .collect();
+            starts.sort();
+            assert_eq!(starts, kmp_search(haystack, pattern));
+        }
+    }
+
+    #[test]
+    fn empty_inputs() {
+        let ac = AhoCorasick::new(["", "a"]);
+        let matches = ac.find_all("aa");
+        assert_eq!(matches.iter().filter(|m| m.pattern == 0).count(), 3);
+        assert_eq!(matches.iter().filter(|m| m.pattern == 1).count(), 2);
+
+        let ac = AhoCorasick::new(Vec::<&str>::new());
+        assert!(ac.find_all("anything").is_empty());
+        assert!(!ac.is_match("anything"));
+    }
+}
@@ -0,0 +1,1482 @@
+#![forbid(unsafe_code)]
+
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
+    marker::PhantomData,
+    mem,
+    ops::{Bound, RangeBounds},
+    sync::atomic::{AtomicU64, Ordering as AtomicOrdering},
+};
+
+const NIL: usize = usize::MAX;
+
+static NEXT_LIST_ID: AtomicU64 = AtomicU64::new(1);
+
+enum Entry<T> {
+    Occupied { elem: T, prev: usize, next: usize },
+    Free { next_free: usize },
+}
+
+struct Slot<T> {
+    entry: Entry<T>,
+    generation: u64,
+}
+
+// Same shape as `DoublyLinkedList`, but nodes live in a `Vec` and link to each other by
+// index, with freed slots chained into a free list. Every slot carries a generation so that
+// handles to removed entries are rejected, exactly like `NodeHandle`.
+//
+// The API matches `DoublyLinkedList`, but some costs differ because entries can't move
+// between arenas. `append`, `prepend`, `splice` and the cursor splices cost O(len of the
+// other list) unless `self` is empty, in which case they adopt the other arena whole.
+// `split_off`, `drain` and the cursor splits cost O(elements split off). The linked list
+// relinks in O(1) for all of these. `iter_mut` allocates O(len) to hand out disjoint
+// borrows without unsafe code.
+pub struct ArenaList<T> {
+    slots: Vec<Slot<T>>,
+    head: usize,
+    tail: usize,
+    free: usize,
+    len: usize,
+    id: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArenaHandle {
+    list: u64,
+    index: usize,
+    generation: u64,
+}
+
+pub struct ArenaListIter<'a, T> {
+    list: &'a ArenaList<T>,
+    front: usize,
+    back: usize,
+    len: usize,
+}
+
+pub struct ArenaListIterMut<'a, T> {
+    inner: std::vec::IntoIter<Option<&'a mut T>>,
+}
+
+pub struct IntoArenaListIter<T> {
+    list: ArenaList<T>,
+}
+
+pub struct ArenaListExtractIf<'a, T, F> {
+    list: &'a mut ArenaList<T>,
+    current: usize,
+    pred: F,
+}
+
+pub struct ArenaListDrain<'a, T> {
+    list: ArenaList<T>,
+    _boo: PhantomData<&'a mut ArenaList<T>>,
+}
+
+pub struct ArenaListCursor<'a, T> {
+    list: &'a ArenaList<T>,
+    current: usize,
+    index: usize,
+}
+
+pub struct ArenaListCursorMut<'a, T> {
+    list: &'a mut ArenaList<T>,
+    current: usize,
+    index: usize,
+}
+
+impl<T> ArenaList<T> {
+    pub fn new() -> ArenaList<T> {
+        ArenaList::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> ArenaList<T> {
+        ArenaList {
+            slots: Vec::with_capacity(capacity),
+            head: NIL,
+            tail: NIL,
+            free: NIL,
+            len: 0,
+            id: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    fn links(&self, idx: usize) -> (usize, usize) {
+        match self.slots[idx].entry {
+            Entry::Occupied { prev, next, .. } => (prev, next),
+            Entry::Free { .. } => unreachable!("linked to a free slot"),
+        }
+    }
+
+    fn set_prev(&mut self, idx: usize, value: usize) {
+        match idx {
+            NIL => self.tail = value,
+            _ => match &mut self.slots[idx].entry {
+                Entry::Occupied { prev, .. } => *prev = value,
+                Entry::Free { .. } => unreachable!("linked to a free slot"),
+            },
+        }
+    }
+
+    fn set_next(&mut self, idx: usize, value: usize) {
+        match idx {
+            NIL => self.head = value,
+            _ => match &mut self.slots[idx].entry {
+                Entry::Occupied { next, .. } => *next = value,
+                Entry::Free { .. } => unreachable!("linked to a free slot"),
+            },
+        }
+    }
+
+    fn elem(&self, idx: usize) -> &T {
+        match &self.slots[idx].entry {
+            Entry::Occupied { elem, .. } => elem,
+            Entry::Free { .. } => unreachable!("linked to a free slot"),
+        }
+    }
+
+    fn elem_mut(&mut self, idx: usize) -> &mut T {
+        match &mut self.slots[idx].entry {
+            Entry::Occupied { elem, .. } => elem,
+            Entry::Free { .. } => unreachable!("linked to a free slot"),
+        }
+    }
+
+    // Inserts a new entry between `prev` and `next`, which must be adjacent (NIL for the ends).
+    fn insert_between(&mut self, elem: T, prev: usize, next: usize) -> usize {
+        let entry = Entry::Occupied { elem, prev, next };
+        let idx = match self.free {
+            NIL => {
+                self.slots.push(Slot {
+                    entry,
+                    generation: 0,
+                });
+                self.slots.len() - 1
+            }
+            idx => {
+                let old = mem::replace(&mut self.slots[idx].entry, entry);
+                self.free = match old {
+                    Entry::Free { next_free } => next_free,
+                    Entry::Occupied { .. } => unreachable!("free list points at a live slot"),
+                };
+                idx
+            }
+        };
+        self.set_next(prev, idx);
+        self.set_prev(next, idx);
+        self.len += 1;
+        idx
+    }
+
+    fn detach(&mut self, idx: usize) {
+        let (prev, next) = self.links(idx);
+        self.set_next(prev, next);
+        self.set_prev(next, prev);
+        self.len -= 1;
+    }
+
+    fn reattach(&mut self, idx: usize, prev: usize, next: usize) {
+        if let Entry::Occupied {
+            prev: old_prev,
+            next: old_next,
+            ..
+        } = &mut self.slots[idx].entry
+        {
+            *old_prev = prev;
+            *old_next = next;
+        }
+        self.set_next(prev, idx);
+        self.set_prev(next, idx);
+        self.len += 1;
+    }
+
+    fn unlink(&mut self, idx: usize) -> T {
+        self.detach(idx);
+        let slot = &mut self.slots[idx];
+        slot.generation += 1;
+        let free = Entry::Free {
+            next_free: self.free,
+        };
+        self.free = idx;
+        match mem::replace(&mut slot.entry, free) {
+            Entry::Occupied { elem, .. } => elem,
+            Entry::Free { .. } => unreachable!("unlinked a free slot"),
+        }
+    }
+
+    fn index_at(&self, index: usize) -> usize {
+        if index >= self.len {
+            return NIL;
+        }
+        if index <= self.len / 2 {
+            (0..index).fold(self.head, |idx, _| self.links(idx).1)
+        } else {
+            (index + 1..self.len).fold(self.tail, |idx, _| self.links(idx).0)
+        }
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        self.insert_between(elem, NIL, self.head);
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        self.insert_between(elem, self.tail, NIL);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        match self.head {
+            NIL => None,
+            idx => Some(self.unlink(idx)),
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        match self.tail {
+            NIL => None,
+            idx => Some(self.unlink(idx)),
+        }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        (self.head != NIL).then(|| self.elem(self.head))
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        match self.head {
+            NIL => None,
+            idx => Some(self.elem_mut(idx)),
+        }
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        (self.tail != NIL).then(|| self.elem(self.tail))
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        match self.tail {
+            NIL => None,
+            idx => Some(self.elem_mut(idx)),
+        }
+    }
+
+    pub fn remove_at(&mut self, index: usize) -> Option<T> {
+        match self.index_at(index) {
+            NIL => None,
+            idx => Some(self.unlink(idx)),
+        }
+    }
+
+    pub fn insert_at(&mut self, elem: T, index: usize) -> Result<(), T> {
+        if index > self.len {
+            return Err(elem);
+        }
+        let next = self.index_at(index);
+        let prev = match next {
+            NIL => self.tail,
+            next => self.links(next).0,
+        };
+        self.insert_between(elem, prev, next);
+        Ok(())
+    }
+
+    pub fn append(&mut self, other: &mut ArenaList<T>) {
+        if self.adopt(other) {
+            return;
+        }
+        while let Some(elem) = other.pop_front() {
+            self.push_back(elem);
+        }
+    }
+
+    pub fn prepend(&mut self, other: &mut ArenaList<T>) {
+        if self.adopt(other) {
+            return;
+        }
+        while let Some(elem) = other.pop_back() {
+            self.push_front(elem);
+        }
+    }
+
+    // Takes over `other`'s arena when `self` has nothing to keep. Handles to the moved entries
+    // stop resolving, as they would in `DoublyLinkedList`.
+    fn adopt(&mut self, other: &mut ArenaList<T>) -> bool {
+        if !self.is_empty() {
+            return false;
+        }
+        mem::swap(self, other);
+        self.id = 0;
+        other.id = 0;
+        true
+    }
+
+    pub fn split_off(&mut self, at: usize) -> ArenaList<T> {
+        assert!(at <= self.len, "Cannot split off at a nonexistent index");
+        if at == 0 {
+            let mut tail = ArenaList::new();
+            tail.adopt(self);
+            return tail;
+        }
+        let mut tail = ArenaList::with_capacity(self.len - at);
+        while self.len > at {
+            tail.push_front(self.pop_back().unwrap());
+        }
+        tail
+    }
+
+    pub fn splice(&mut self, at: usize, mut other: ArenaList<T>) {
+        assert!(at <= self.len, "Cannot splice at a nonexistent index");
+        if self.adopt(&mut other) {
+            return;
+        }
+        let next = self.index_at(at);
+        let mut prev = match next {
+            NIL => self.tail,
+            next => self.links(next).0,
+        };
+        while let Some(elem) = other.pop_front() {
+            prev = self.insert_between(elem, prev, next);
+        }
+    }
+
+    pub fn merge(&mut self, other: &mut ArenaList<T>)
+    where
+        T: Ord,
+    {
+        self.merge_by(other, T::cmp);
+    }
+
+    pub fn merge_by<F>(&mut self, other: &mut ArenaList<T>, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut curr = self.head;
+        while curr != NIL {
+            match other.front() {
+                Some(candidate) if compare(candidate, self.elem(curr)) == Ordering::Less => {
+                    let elem = other.pop_front().unwrap();
+                    let prev = self.links(curr).0;
+                    self.insert_between(elem, prev, curr);
+                }
+                Some(_) => curr = self.links(curr).1,
+                None => return,
+            }
+        }
+        self.append(other);
+    }
+
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    // A stable bottom-up merge sort that only relinks entries, so handles stay valid and no
+    // scratch memory is needed. Runs are merged along the `next` links alone, and the `prev`
+    // links are rebuilt once at the end.
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut width = 1;
+        loop {
+            let mut left = mem::replace(&mut self.head, NIL);
+            let mut tail = NIL;
+            let mut merges = 0;
+            while left != NIL {
+                merges += 1;
+                let mut right = left;
+                let mut left_len = 0;
+                while left_len < width && right != NIL {
+                    left_len += 1;
+                    right = self.links(right).1;
+                }
+                let mut right_len = width;
+                while left_len > 0 || (right_len > 0 && right != NIL) {
+                    let take_left = left_len > 0
+                        && (right_len == 0
+                            || right == NIL
+                            || compare(self.elem(left), self.elem(right)) != Ordering::Greater);
+                    let idx = if take_left {
+                        left_len -= 1;
+                        let idx = left;
+                        left = self.links(idx).1;
+                        idx
+                    } else {
+                        right_len -= 1;
+                        let idx = right;
+                        right = self.links(idx).1;
+                        idx
+                    };
+                    self.set_next(tail, idx);
+                    tail = idx;
+                }
+                left = right;
+            }
+            if tail != NIL {
+                self.set_next(tail, NIL);
+            }
+            if merges <= 1 {
+                break;
+            }
+            width *= 2;
+        }
+
+        let mut prev = NIL;
+        let mut curr = self.head;
+        while curr != NIL {
+            self.set_prev(curr, prev);
+            prev = curr;
+            curr = self.links(curr).1;
+        }
+        self.tail = prev;
+    }
+
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        K: PartialEq,
+        F: FnMut(&mut T) -> K,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        if self.head == NIL {
+            return;
+        }
+        let mut kept = self.head;
+        loop {
+            let next = self.links(kept).1;
+            if next == NIL {
+                return;
+            }
+            let (a, b) = two_elems_mut(&mut self.slots, next, kept);
+            if same_bucket(a, b) {
+                self.unlink(next);
+            } else {
+                kept = next;
+            }
+        }
+    }
+
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|elem| f(elem));
+    }
+
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let mut curr = self.head;
+        while curr != NIL {
+            let next = self.links(curr).1;
+            if !f(self.elem_mut(curr)) {
+                self.unlink(curr);
+            }
+            curr = next;
+        }
+    }
+
+    pub fn extract_if<F>(&mut self, pred: F) -> ArenaListExtractIf<'_, T, F>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        ArenaListExtractIf {
+            current: self.head,
+            list: self,
+            pred,
+        }
+    }
+
+    pub fn drain<R>(&mut self, range: R) -> ArenaListDrain<'_, T>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+        assert!(
+            start <= end && end <= self.len,
+            "Cannot drain a range outside the list"
+        );
+        let mut drained = ArenaList::with_capacity(end - start);
+        let mut curr = self.index_at(start);
+        for _ in start..end {
+            let next = self.links(curr).1;
+            drained.push_back(self.unlink(curr));
+            curr = next;
+        }
+        ArenaListDrain {
+            list: drained,
+            _boo: PhantomData,
+        }
+    }
+
+    fn handle_for(&mut self, idx: usize) -> ArenaHandle {
+        if self.id == 0 {
+            self.id = NEXT_LIST_ID.fetch_add(1, AtomicOrdering::Relaxed);
+        }
+        ArenaHandle {
+            list: self.id,
+            index: idx,
+            generation: self.slots[idx].generation,
+        }
+    }
+
+    fn resolve(&self, handle: ArenaHandle) -> Option<usize> {
+        if self.id == 0 || handle.list != self.id {
+            return None;
+        }
+        let slot = self.slots.get(handle.index)?;
+        match slot.entry {
+            Entry::Occupied { .. } if slot.generation == handle.generation => Some(handle.index),
+            _ => None,
+        }
+    }
+
+    pub fn push_front_handle(&mut self, elem: T) -> ArenaHandle {
+        let idx = self.insert_between(elem, NIL, self.head);
+        self.handle_for(idx)
+    }
+
+    pub fn push_back_handle(&mut self, elem: T) -> ArenaHandle {
+        let idx = self.insert_between(elem, self.tail, NIL);
+        self.handle_for(idx)
+    }
+
+    pub fn get(&self, handle: ArenaHandle) -> Option<&T> {
+        self.resolve(handle).map(|idx| self.elem(idx))
+    }
+
+    pub fn get_mut(&mut self, handle: ArenaHandle) -> Option<&mut T> {
+        let idx = self.resolve(handle)?;
+        Some(self.elem_mut(idx))
+    }
+
+    pub fn remove(&mut self, handle: ArenaHandle) -> Option<T> {
+        let idx = self.resolve(handle)?;
+        Some(self.unlink(idx))
+    }
+
+    pub fn move_to_front(&mut self, handle: ArenaHandle) -> bool {
+        let Some(idx) = self.resolve(handle) else {
+            return false;
+        };
+        self.detach(idx);
+        self.reattach(idx, NIL, self.head);
+        true
+    }
+
+    pub fn move_to_back(&mut self, handle: ArenaHandle) -> bool {
+        let Some(idx) = self.resolve(handle) else {
+            return false;
+        };
+        self.detach(idx);
+        self.reattach(idx, self.tail, NIL);
+        true
+    }
+
+    pub fn cursor_front(&self) -> ArenaListCursor<'_, T> {
+        ArenaListCursor {
+            list: self,
+            current: self.head,
+            index: 0,
+        }
+    }
+
+    pub fn cursor_back(&self) -> ArenaListCursor<'_, T> {
+        ArenaListCursor {
+            list: self,
+            current: self.tail,
+            index: self.len.saturating_sub(1),
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> ArenaListCursorMut<'_, T> {
+        ArenaListCursorMut {
+            current: self.head,
+            index: 0,
+            list: self,
+        }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> ArenaListCursorMut<'_, T> {
+        ArenaListCursorMut {
+            current: self.tail,
+            index: self.len.saturating_sub(1),
+            list: self,
+        }
+    }
+
+    pub fn iter(&self) -> ArenaListIter<'_, T> {
+        ArenaListIter {
+            list: self,
+            front: self.head,
+            back: self.tail,
+            len: self.len,
+        }
+    }
+
+    // Handing out several `&mut T` into one `Vec` without unsafe means splitting the borrow
+    // up front. Each entry's position is parked in its `prev` link for a single pass over the
+    // slots, which puts the link back as it takes the element.
+    pub fn iter_mut(&mut self) -> ArenaListIterMut<'_, T> {
+        let mut order = Vec::with_capacity(self.len);
+        let mut curr = self.head;
+        while curr != NIL {
+            let next = self.links(curr).1;
+            self.set_prev(curr, order.len());
+            order.push(curr);
+            curr = next;
+        }
+        let mut ordered: Vec<Option<&mut T>> = (0..self.len).map(|_| None).collect();
+        for slot in &mut self.slots {
+            if let Entry::Occupied { elem, prev, .. } = &mut slot.entry {
+                let position = *prev;
+                *prev = position.checked_sub(1).map_or(NIL, |before| order[before]);
+                ordered[position] = Some(elem);
+            }
+        }
+        ArenaListIterMut {
+            inner: ordered.into_iter(),
+        }
+    }
+
+    pub fn into_iter(self) -> IntoArenaListIter<T> {
+        IntoArenaListIter { list: self }
+    }
+}
+
+fn two_elems_mut<T>(slots: &mut [Slot<T>], a: usize, b: usize) -> (&mut T, &mut T) {
+    let (first, second) = if a < b {
+        let (lo, hi) = slots.split_at_mut(b);
+        (&mut lo[a], &mut hi[0])
+    } else {
+        let (lo, hi) = slots.split_at_mut(a);
+        (&mut hi[0], &mut lo[b])
+    };
+    match (&mut first.entry, &mut second.entry) {
+        (Entry::Occupied { elem: a, .. }, Entry::Occupied { elem: b, .. }) => (a, b),
+        _ => unreachable!("linked to a free slot"),
+    }
+}
+
+impl<T> Default for ArenaList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for ArenaList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArenaList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for ArenaList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for ArenaList<T> {}
+
+impl<T: PartialOrd> PartialOrd for ArenaList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for ArenaList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for ArenaList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        for elem in self {
+            elem.hash(state);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for ArenaList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = ArenaList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for ArenaList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a> Extend<&'a T> for ArenaList<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ArenaList<T> {
+    type Item = &'a T;
+
+    type IntoIter = ArenaListIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Clone for ArenaListIter<'a, T> {
+    fn clone(&self) -> Self {
+        ArenaListIter { ..*self }
+    }
+}
+
+impl<'a, T> Iterator for ArenaListIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            let idx = self.front;
+            self.len -= 1;
+            self.front = self.list.links(idx).1;
+            Some(self.list.elem(idx))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for ArenaListIter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            let idx = self.back;
+            self.len -= 1;
+            self.back = self.list.links(idx).0;
+            Some(self.list.elem(idx))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for ArenaListIter<'a, T> {}
+
+impl<'a, T> FusedIterator for ArenaListIter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a mut ArenaList<T> {
+    type IntoIter = ArenaListIterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for ArenaListIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().flatten()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for ArenaListIterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().flatten()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for ArenaListIterMut<'a, T> {}
+
+impl<'a, T> FusedIterator for ArenaListIterMut<'a, T> {}
+
+impl<T> IntoIterator for ArenaList<T> {
+    type IntoIter = IntoArenaListIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_iter()
+    }
+}
+
+impl<T> Iterator for IntoArenaListIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoArenaListIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoArenaListIter<T> {}
+
+impl<T> FusedIterator for IntoArenaListIter<T> {}
+
+impl<'a, T, F> Iterator for ArenaListExtractIf<'a, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current != NIL {
+            let idx = self.current;
+            self.current = self.list.links(idx).1;
+            if (self.pred)(self.list.elem_mut(idx)) {
+                return Some(self.list.unlink(idx));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.list.len))
+    }
+}
+
+impl<'a, T, F> FusedIterator for ArenaListExtractIf<'a, T, F> where F: FnMut(&mut T) -> bool {}
+
+impl<'a, T> Iterator for ArenaListDrain<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for ArenaListDrain<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for ArenaListDrain<'a, T> {}
+
+impl<'a, T> FusedIterator for ArenaListDrain<'a, T> {}
+
+impl<'a, T: fmt::Debug> fmt::Debug for ArenaListDrain<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ArenaListDrain").field(&self.list).finish()
+    }
+}
+
+// Same ghost position as the `DoublyLinkedList` cursors, with `NIL` standing in for it.
+impl<'a, T> Clone for ArenaListCursor<'a, T> {
+    fn clone(&self) -> Self {
+        ArenaListCursor { ..*self }
+    }
+}
+
+impl<'a, T> ArenaListCursor<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        (self.current != NIL).then_some(self.index)
+    }
+
+    pub fn move_next(&mut self) {
+        match self.current {
+            NIL => {
+                self.current = self.list.head;
+                self.index = 0;
+            }
+            idx => {
+                self.current = self.list.links(idx).1;
+                self.index += 1;
+            }
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        match self.current {
+            NIL => {
+                self.current = self.list.tail;
+                self.index = self.list.len.saturating_sub(1);
+            }
+            idx => {
+                self.current = self.list.links(idx).0;
+                self.index = self.index.checked_sub(1).unwrap_or(self.list.len);
+            }
+        }
+    }
+
+    pub fn current(&self) -> Option<&'a T> {
+        let list = self.list;
+        (self.current != NIL).then(|| list.elem(self.current))
+    }
+
+    pub fn peek_next(&self) -> Option<&'a T> {
+        let list = self.list;
+        let next = match self.current {
+            NIL => list.head,
+            idx => list.links(idx).1,
+        };
+        (next != NIL).then(|| list.elem(next))
+    }
+
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        let list = self.list;
+        let prev = match self.current {
+            NIL => list.tail,
+            idx => list.links(idx).0,
+        };
+        (prev != NIL).then(|| list.elem(prev))
+    }
+}
+
+impl<'a, T> ArenaListCursorMut<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        (self.current != NIL).then_some(self.index)
+    }
+
+    pub fn move_next(&mut self) {
+        let mut cursor = self.as_cursor();
+        cursor.move_next();
+        (self.current, self.index) = (cursor.current, cursor.index);
+    }
+
+    pub fn move_prev(&mut self) {
+        let mut cursor = self.as_cursor();
+        cursor.move_prev();
+        (self.current, self.index) = (cursor.current, cursor.index);
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        match self.current {
+            NIL => None,
+            idx => Some(self.list.elem_mut(idx)),
+        }
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        match self.next_idx() {
+            NIL => None,
+            idx => Some(self.list.elem_mut(idx)),
+        }
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        match self.prev_idx() {
+            NIL => None,
+            idx => Some(self.list.elem_mut(idx)),
+        }
+    }
+
+    fn next_idx(&self) -> usize {
+        match self.current {
+            NIL => self.list.head,
+            idx => self.list.links(idx).1,
+        }
+    }
+
+    fn prev_idx(&self) -> usize {
+        match self.current {
+            NIL => self.list.tail,
+            idx => self.list.links(idx).0,
+        }
+    }
+
+    pub fn as_cursor(&self) -> ArenaListCursor<'_, T> {
+        ArenaListCursor {
+            list: self.list,
+            current: self.current,
+            index: self.index,
+        }
+    }
+
+    pub fn insert_after(&mut self, elem: T) {
+        let next = self.next_idx();
+        self.list.insert_between(elem, self.current, next);
+        if self.current == NIL {
+            self.index += 1;
+        }
+    }
+
+    pub fn insert_before(&mut self, elem: T) {
+        let prev = self.prev_idx();
+        self.list.insert_between(elem, prev, self.current);
+        self.index += 1;
+    }
+
+    pub fn remove_current(&mut self) -> Option<T> {
+        if self.current == NIL {
+            return None;
+        }
+        let next = self.list.links(self.current).1;
+        let elem = self.list.unlink(self.current);
+        self.current = next;
+        Some(elem)
+    }
+
+    // As with `ArenaList::split_off`, splitting and splicing move the elements one by one.
+    pub fn split_after(&mut self) -> ArenaList<T> {
+        let mut split = ArenaList::new();
+        loop {
+            let next = self.next_idx();
+            if next == NIL {
+                break;
+            }
+            split.push_back(self.list.unlink(next));
+        }
+        if self.current == NIL {
+            self.index = 0;
+        }
+        split
+    }
+
+    pub fn split_before(&mut self) -> ArenaList<T> {
+        let mut split = ArenaList::new();
+        loop {
+            let prev = self.prev_idx();
+            if prev == NIL {
+                break;
+            }
+            split.push_front(self.list.unlink(prev));
+        }
+        self.index = 0;
+        split
+    }
+
+    pub fn splice_after(&mut self, mut other: ArenaList<T>) {
+        let next = self.next_idx();
+        if self.current == NIL {
+            self.index += other.len;
+        }
+        let mut prev = self.current;
+        while let Some(elem) = other.pop_front() {
+            prev = self.list.insert_between(elem, prev, next);
+        }
+    }
+
+    pub fn splice_before(&mut self, mut other: ArenaList<T>) {
+        let mut prev = self.prev_idx();
+        self.index += other.len;
+        while let Some(elem) = other.pop_front() {
+            prev = self.list.insert_between(elem, prev, self.current);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linked_list::DoublyLinkedList;
+    use rand::Rng;
+
+    fn contents(list: &ArenaList<i32>) -> Vec<i32> {
+        let forward: Vec<i32> = list.iter().copied().collect();
+        let mut backward: Vec<i32> = list.iter().rev().copied().collect();
+        backward.reverse();
+        assert_eq!(forward, backward);
+        assert_eq!(forward.len(), list.len());
+        forward
+    }
+
+    #[test]
+    fn it_works() {
+        let mut list = ArenaList::new();
+        assert_eq!(list.pop_front(), None);
+        list.push_front(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_eq!(contents(&list), vec![1, 2, 3]);
+        assert_eq!(list.front(), Some(&1));
+        assert_eq!(list.back(), Some(&3));
+        *list.front_mut().unwrap() = 10;
+        *list.back_mut().unwrap() = 30;
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq!(list.pop_back(), Some(30));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_back(), None);
+        assert!(list.is_empty());
+
+        for i in 0..4 {
+            list.push_back(i);
+        }
+        // Freed slots are reused instead of growing the arena.
+        assert_eq!(list.slots.len(), 4);
+        for item in &mut list {
+            *item *= 2;
+        }
+        let mut iter = list.iter_mut();
+        *iter.next_back().unwrap() += 1;
+        assert_eq!(contents(&list), vec![0, 2, 4, 7]);
+        assert_eq!(
+            list.clone().into_iter().rev().collect::<Vec<_>>(),
+            vec![7, 4, 2, 0]
+        );
+        assert_eq!(format!("{:?}", list), "[0, 2, 4, 7]");
+    }
+
+    #[test]
+    fn matches_doubly_linked_list() {
+        let mut rng = rand::thread_rng();
+        let mut arena = ArenaList::new();
+        let mut pointers = DoublyLinkedList::new();
+        for step in 0..5000 {
+            let index = rng.gen_range(0..=arena.len() + 1);
+            match rng.gen_range(0..8) {
+                0 => {
+                    arena.push_front(step);
+                    pointers.push_front(step);
+                }
+                1 => {
+                    arena.push_back(step);
+                    pointers.push_back(step);
+                }
+                2 => assert_eq!(arena.pop_front(), pointers.pop_front()),
+                3 => assert_eq!(arena.pop_back(), pointers.pop_back()),
+                4 => assert_eq!(arena.remove_at(index), pointers.remove_at(index)),
+                5 => assert_eq!(
+                    arena.insert_at(step, index),
+                    pointers.insert_at(step, index)
+                ),
+                6 if index <= arena.len() => {
+                    let mut tail = arena.split_off(index);
+                    let mut other = pointers.split_off(index);
+                    assert_eq!(
+                        tail.iter().collect::<Vec<_>>(),
+                        other.iter().collect::<Vec<_>>()
+                    );
+                    arena.prepend(&mut tail);
+                    pointers.prepend(&mut other);
+                }
+                _ => {
+                    arena.dedup_by_key(|x| *x / 64);
+                    pointers.dedup_by_key(|x| *x / 64);
+                }
+            }
+            assert_eq!(arena.front(), pointers.front());
+            assert_eq!(arena.back(), pointers.back());
+            assert_eq!(arena.len(), pointers.len());
+        }
+        assert_eq!(
+            arena.iter().collect::<Vec<_>>(),
+            pointers.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn append_splice_merge_sort() {
+        let mut list: ArenaList<i32> = [3, 4].into_iter().collect();
+        list.append(&mut [5, 6].into_iter().collect());
+        list.prepend(&mut [1, 2].into_iter().collect());
+        list.splice(3, [10, 11].into_iter().collect());
+        assert_eq!(contents(&list), vec![1, 2, 3, 10, 11, 4, 5, 6]);
+        let tail = list.split_off(3);
+        assert_eq!(contents(&tail), vec![10, 11, 4, 5, 6]);
+
+        let mut other: ArenaList<i32> = [0, 2, 7].into_iter().collect();
+        list.merge(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(contents(&list), vec![0, 1, 2, 2, 3, 7]);
+
+        let mut list: ArenaList<i32> = [5, 1, 4, 1, 3].into_iter().collect();
+        let handle = list.push_back_handle(2);
+        list.sort();
+        assert_eq!(contents(&list), vec![1, 1, 2, 3, 4, 5]);
+        assert_eq!(list.get(handle), Some(&2));
+        list.sort_by_key(|x| -x);
+        assert_eq!(contents(&list), vec![5, 4, 3, 2, 1, 1]);
+        list.dedup();
+        assert_eq!(contents(&list), vec![5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn handles() {
+        let mut list = ArenaList::new();
+        let one = list.push_back_handle(1);
+        let two = list.push_back_handle(2);
+        let zero = list.push_front_handle(0);
+        assert!(list.move_to_back(zero));
+        assert!(list.move_to_front(two));
+        *list.get_mut(one).unwrap() = 10;
+        assert_eq!(contents(&list), vec![2, 10, 0]);
+
+        assert_eq!(list.remove(one), Some(10));
+        assert_eq!(list.remove(one), None);
+        let reused = list.push_back_handle(3);
+        assert_eq!(reused.index, one.index);
+        assert_eq!(list.get(one), None);
+        assert_eq!(list.get(reused), Some(&3));
+        assert!(!list.move_to_front(one));
+
+        let other: ArenaList<i32> = [1, 2, 3].into_iter().collect();
+        assert_eq!(other.get(two), None);
+        assert_eq!(list.clone().get(two), None);
+    }
+
+    #[test]
+    fn retain_extract_if_drain() {
+        let mut list: ArenaList<i32> = (0..10).collect();
+        list.retain(|x| x % 3 != 0);
+        assert_eq!(contents(&list), vec![1, 2, 4, 5, 7, 8]);
+        list.retain_mut(|x| {
+            *x *= 10;
+            *x != 40
+        });
+        assert_eq!(contents(&list), vec![10, 20, 50, 70, 80]);
+
+        assert_eq!(list.extract_if(|x| *x > 40).next(), Some(50));
+        assert_eq!(contents(&list), vec![10, 20, 70, 80]);
+        assert_eq!(
+            list.extract_if(|x| *x > 40).collect::<Vec<_>>(),
+            vec![70, 80]
+        );
+
+        let mut list: ArenaList<i32> = (0..6).collect();
+        let mut drain = list.drain(1..=3);
+        assert_eq!(drain.len(), 3);
+        assert_eq!(drain.next_back(), Some(3));
+        assert_eq!(format!("{:?}", drain), "ArenaListDrain([1, 2])");
+        drop(drain);
+        assert_eq!(contents(&list), vec![0, 4, 5]);
+        assert_eq!(list.drain(..).collect::<Vec<_>>(), vec![0, 4, 5]);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot drain a range outside the list")]
+    fn drain_out_of_bounds() {
+        let mut list: ArenaList<i32> = (0..3).collect();
+        list.drain(2..4);
+    }
+
+    #[test]
+    fn cursors() {
+        let list: ArenaList<i32> = (1..=3).collect();
+        let mut cursor = list.cursor_back();
+        assert_eq!((cursor.index(), cursor.current()), (Some(2), Some(&3)));
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current()), (None, None));
+        assert_eq!(
+            (cursor.peek_next(), cursor.peek_prev()),
+            (Some(&1), Some(&3))
+        );
+        let mut copy = cursor.clone();
+        copy.move_prev();
+        assert_eq!(copy.current(), Some(&3));
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current()), (Some(0), Some(&1)));
+
+        let mut list: ArenaList<i32> = (1..=5).collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        *cursor.current().unwrap() = 20;
+        cursor.insert_before(15);
+        cursor.insert_after(25);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.remove_current(), Some(20));
+        assert_eq!(cursor.current(), Some(&mut 25));
+        assert_eq!(cursor.as_cursor().peek_prev(), Some(&15));
+        let tail = cursor.split_after();
+        assert_eq!(contents(&tail), vec![3, 4, 5]);
+        let head = cursor.split_before();
+        assert_eq!(contents(&head), vec![1, 15]);
+        assert_eq!(cursor.index(), Some(0));
+        cursor.splice_before(head);
+        cursor.splice_after(tail);
+        assert_eq!(cursor.index(), Some(2));
+        cursor.move_prev();
+        assert_eq!(cursor.peek_next(), Some(&mut 25));
+        assert_eq!(contents(&list), vec![1, 15, 25, 3, 4, 5]);
+    }
+
+    #[test]
+    fn cursors_match_doubly_linked_list() {
+        let mut rng = rand::thread_rng();
+        let mut arena: ArenaList<i32> = ArenaList::new();
+        let mut pointers: DoublyLinkedList<i32> = DoublyLinkedList::new();
+        let mut ours = arena.cursor_front_mut();
+        let mut theirs = pointers.cursor_front_mut();
+        for step in 0..5000 {
+            match rng.gen_range(0..10) {
+                0 | 1 => {
+                    ours.move_next();
+                    theirs.move_next();
+                }
+                2 | 3 => {
+                    ours.move_prev();
+                    theirs.move_prev();
+                }
+                4 => {
+                    ours.insert_after(step);
+                    theirs.insert_after(step);
+                }
+                5 => {
+                    ours.insert_before(step);
+                    theirs.insert_before(step);
+                }
+                6 => assert_eq!(ours.remove_current(), theirs.remove_current()),
+                7 => {
+                    let split = ours.split_after();
+                    let other = theirs.split_after();
+                    assert!(split.iter().eq(other.iter()));
+                    ours.splice_after(split);
+                    theirs.splice_after(other);
+                }
+                8 => {
+                    let split = ours.split_before();
+                    let other = theirs.split_before();
+                    assert!(split.iter().eq(other.iter()));
+                    ours.splice_before(split);
+                    theirs.splice_before(other);
+                }
+                _ => assert_eq!(ours.peek_next(), theirs.peek_next()),
+            }
+            assert_eq!(ours.index(), theirs.index());
+            assert_eq!(ours.current(), theirs.current());
+            assert_eq!(ours.as_cursor().peek_prev(), theirs.as_cursor().peek_prev());
+        }
+        assert!(arena.iter().eq(pointers.iter()));
+    }
+
+    #[test]
+    fn sort_is_stable_and_keeps_handles() {
+        let mut rng = rand::thread_rng();
+        for len in [0, 1, 2, 3, 7, 64, 100, 1000] {
+            let mut list = ArenaList::new();
+            let mut handles = Vec::new();
+            for i in 0..len {
+                let key = rng.gen_range(0..len / 3 + 1);
+                handles.push((list.push_back_handle((key, i)), (key, i)));
+            }
+            // Leave some free slots behind so the links don't follow slot order.
+            for (handle, _) in handles.iter().step_by(5) {
+                list.remove(*handle);
+            }
+            let mut expected: Vec<(usize, usize)> = list.iter().copied().collect();
+            expected.sort_by_key(|&(key, _)| key);
+
+            list.sort_by_key(|&(key, _)| key);
+            assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+            let mut backward: Vec<(usize, usize)> = list.iter().rev().copied().collect();
+            backward.reverse();
+            assert_eq!(backward, expected);
+            for (i, (handle, elem)) in handles.iter().enumerate() {
+                let live = (i % 5 != 0).then_some(elem);
+                assert_eq!(list.get(*handle), live);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_lists_adopt_the_other_arena() {
+        let mut empty = ArenaList::new();
+        let mut other = ArenaList::new();
+        let handle = other.push_back_handle(1);
+        other.push_back(2);
+        empty.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(contents(&empty), vec![1, 2]);
+        assert_eq!(empty.get(handle), None);
+        assert_eq!(other.get(handle), None);
+
+        let mut front = ArenaList::new();
+        front.prepend(&mut empty);
+        let mut spliced = ArenaList::new();
+        spliced.splice(0, front.split_off(0));
+        assert!(front.is_empty());
+        assert_eq!(contents(&spliced), vec![1, 2]);
+        spliced.push_front(0);
+        assert_eq!(contents(&spliced), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn standard_traits() {
+        let a: ArenaList<i32> = (1..=3).collect();
+        let mut b = ArenaList::new();
+        b.extend(&[1, 2]);
+        assert!(b < a);
+        b.extend(vec![3]);
+        assert_eq!(a, b);
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+
+        let mut hashes = std::collections::HashSet::new();
+        hashes.insert(a.clone());
+        assert!(hashes.contains(&b));
+        assert_eq!(ArenaList::<i32>::default().len(), 0);
+    }
+}
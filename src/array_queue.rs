@@ -0,0 +1,455 @@
+use std::{fmt, iter::FusedIterator, slice};
+
+// A ring buffer: the `len` elements start at `head` and wrap around the end of `buf`, and
+// every slot outside that window is `None`. Bounded queues never reallocate.
+pub struct ArrayQueue<T> {
+    buf: Vec<Option<T>>,
+    head: usize,
+    len: usize,
+    bounded: bool,
+}
+
+pub struct ArrayQueueIter<'a, T> {
+    front: slice::Iter<'a, Option<T>>,
+    back: slice::Iter<'a, Option<T>>,
+}
+
+pub struct ArrayQueueIterMut<'a, T> {
+    front: slice::IterMut<'a, Option<T>>,
+    back: slice::IterMut<'a, Option<T>>,
+}
+
+pub struct IntoArrayQueueIter<T> {
+    queue: ArrayQueue<T>,
+}
+
+impl<T> ArrayQueue<T> {
+    pub fn new() -> ArrayQueue<T> {
+        ArrayQueue::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> ArrayQueue<T> {
+        ArrayQueue {
+            buf: (0..capacity).map(|_| None).collect(),
+            head: 0,
+            len: 0,
+            bounded: false,
+        }
+    }
+
+    pub fn bounded(capacity: usize) -> ArrayQueue<T> {
+        ArrayQueue {
+            bounded: true,
+            ..ArrayQueue::with_capacity(capacity)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        self.bounded
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == self.buf.len()
+    }
+
+    fn slot(&self, offset: usize) -> usize {
+        (self.head + offset) % self.buf.len()
+    }
+
+    fn grow(&mut self) {
+        let capacity = (self.buf.len() * 2).max(4);
+        let mut buf: Vec<Option<T>> = Vec::with_capacity(capacity);
+        buf.extend((0..self.len).map(|offset| {
+            let slot = self.slot(offset);
+            self.buf[slot].take()
+        }));
+        buf.resize_with(capacity, || None);
+        self.buf = buf;
+        self.head = 0;
+    }
+
+    // Panics if a bounded queue is full; use `try_enqueue` or `enqueue_overwrite` there.
+    pub fn enqueue(&mut self, val: T) {
+        if self.try_enqueue(val).is_err() {
+            panic!("Cannot enqueue into a full bounded queue");
+        }
+    }
+
+    pub fn try_enqueue(&mut self, val: T) -> Result<(), T> {
+        if self.is_full() {
+            if self.bounded {
+                return Err(val);
+            }
+            self.grow();
+        }
+        let slot = self.slot(self.len);
+        self.buf[slot] = Some(val);
+        self.len += 1;
+        Ok(())
+    }
+
+    // On a full bounded queue this makes room by dropping the oldest element, so it always
+    // holds the last `capacity` values, and hands the displaced value back. A growable queue
+    // is never full, so this is plain `enqueue` there.
+    pub fn enqueue_overwrite(&mut self, val: T) -> Option<T> {
+        if !self.bounded || !self.is_full() {
+            self.enqueue(val);
+            return None;
+        }
+        if self.buf.is_empty() {
+            return Some(val);
+        }
+        let old = self.buf[self.head].replace(val);
+        self.head = self.slot(1);
+        old
+    }
+
+    pub fn deque(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let val = self.buf[self.head].take();
+        self.head = self.slot(1);
+        self.len -= 1;
+        val
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.buf.get(self.head).and_then(Option::as_ref)
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.buf.get_mut(self.head).and_then(Option::as_mut)
+    }
+
+    pub fn clear(&mut self) {
+        while self.deque().is_some() {}
+        self.head = 0;
+    }
+
+    fn split_point(&self) -> (usize, usize) {
+        let first = self.len.min(self.buf.len() - self.head);
+        (self.head + first, self.len - first)
+    }
+
+    pub fn iter(&self) -> ArrayQueueIter<'_, T> {
+        if self.len == 0 {
+            return ArrayQueueIter {
+                front: [].iter(),
+                back: [].iter(),
+            };
+        }
+        let (end, wrapped) = self.split_point();
+        ArrayQueueIter {
+            front: self.buf[self.head..end].iter(),
+            back: self.buf[..wrapped].iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> ArrayQueueIterMut<'_, T> {
+        if self.len == 0 {
+            return ArrayQueueIterMut {
+                front: [].iter_mut(),
+                back: [].iter_mut(),
+            };
+        }
+        let (end, wrapped) = self.split_point();
+        let (before, after) = self.buf.split_at_mut(self.head);
+        ArrayQueueIterMut {
+            front: after[..end - self.head].iter_mut(),
+            back: before[..wrapped].iter_mut(),
+        }
+    }
+
+    pub fn into_iter(self) -> IntoArrayQueueIter<T> {
+        IntoArrayQueueIter { queue: self }
+    }
+}
+
+impl<T> Default for ArrayQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Clones keep the capacity and boundedness of the original.
+impl<T: Clone> Clone for ArrayQueue<T> {
+    fn clone(&self) -> Self {
+        let mut queue = ArrayQueue {
+            bounded: self.bounded,
+            ..ArrayQueue::with_capacity(self.capacity())
+        };
+        queue.extend(self.iter().cloned());
+        queue
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArrayQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T> FromIterator<T> for ArrayQueue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut queue = ArrayQueue::new();
+        queue.extend(iter);
+        queue
+    }
+}
+
+impl<T> Extend<T> for ArrayQueue<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.enqueue(val);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a> Extend<&'a T> for ArrayQueue<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ArrayQueue<T> {
+    type Item = &'a T;
+
+    type IntoIter = ArrayQueueIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Clone for ArrayQueueIter<'a, T> {
+    fn clone(&self) -> Self {
+        ArrayQueueIter {
+            front: self.front.clone(),
+            back: self.back.clone(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for ArrayQueueIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front
+            .next()
+            .or_else(|| self.back.next())
+            .map(|slot| slot.as_ref().unwrap())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut ArrayQueue<T> {
+    type IntoIter = ArrayQueueIterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for ArrayQueueIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front
+            .next()
+            .or_else(|| self.back.next())
+            .map(|slot| slot.as_mut().unwrap())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> IntoIterator for ArrayQueue<T> {
+    type IntoIter = IntoArrayQueueIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_iter()
+    }
+}
+
+impl<T> Iterator for IntoArrayQueueIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.queue.deque()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.queue.len, Some(self.queue.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for ArrayQueueIter<'a, T> {}
+
+impl<'a, T> ExactSizeIterator for ArrayQueueIterMut<'a, T> {}
+
+impl<T> ExactSizeIterator for IntoArrayQueueIter<T> {}
+
+impl<'a, T> FusedIterator for ArrayQueueIter<'a, T> {}
+
+impl<'a, T> FusedIterator for ArrayQueueIterMut<'a, T> {}
+
+impl<T> FusedIterator for IntoArrayQueueIter<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::queue::Queue;
+    use rand::Rng;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn it_works() {
+        let mut list = ArrayQueue::new();
+
+        list.enqueue(5);
+        list.enqueue(7);
+        list.enqueue(9);
+
+        assert_eq!(list.deque(), Some(5));
+        assert_eq!(list.len(), 2);
+
+        list.enqueue(11);
+
+        assert_eq!(list.deque(), Some(7));
+        assert_eq!(list.deque(), Some(9));
+        assert_eq!(list.peek(), Some(&11));
+        assert_eq!(list.deque(), Some(11));
+        assert_eq!(list.deque(), None);
+        assert_eq!(list.len(), 0);
+
+        list.enqueue(69);
+        assert_eq!(list.peek(), Some(&69));
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    fn bounded() {
+        let mut queue = ArrayQueue::bounded(3);
+        assert_eq!(queue.try_enqueue(1), Ok(()));
+        assert_eq!(queue.try_enqueue(2), Ok(()));
+        assert_eq!(queue.try_enqueue(3), Ok(()));
+        assert!(queue.is_full());
+        assert_eq!(queue.try_enqueue(4), Err(4));
+        assert_eq!(queue.deque(), Some(1));
+        assert_eq!(queue.try_enqueue(4), Ok(()));
+        assert_eq!(queue.capacity(), 3);
+        assert_eq!(queue.iter().collect::<Vec<_>>(), vec![&2, &3, &4]);
+
+        let result = std::panic::catch_unwind(move || queue.enqueue(5));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn keeps_last_n() {
+        let mut last = ArrayQueue::bounded(3);
+        let displaced: Vec<Option<i32>> = (1..=5).map(|i| last.enqueue_overwrite(i)).collect();
+        assert_eq!(displaced, vec![None, None, None, Some(1), Some(2)]);
+        assert_eq!(format!("{:?}", last), "[3, 4, 5]");
+        for val in &mut last {
+            *val *= 10;
+        }
+        *last.peek_mut().unwrap() += 1;
+        assert_eq!(
+            last.clone().into_iter().collect::<Vec<_>>(),
+            vec![31, 40, 50]
+        );
+        assert_eq!(last.clone().capacity(), 3);
+
+        let mut none = ArrayQueue::bounded(0);
+        assert_eq!(none.enqueue_overwrite(1), Some(1));
+        assert_eq!(none.try_enqueue(1), Err(1));
+        assert_eq!(none.peek(), None);
+        assert!(none.is_empty());
+
+        let mut growable = ArrayQueue::new();
+        for i in 0..10 {
+            assert_eq!(growable.enqueue_overwrite(i), None);
+        }
+        assert_eq!(growable.len(), 10);
+        assert_eq!(growable.deque(), Some(0));
+    }
+
+    #[test]
+    fn swaps_with_queue() {
+        fn drain_alternating<Q>(
+            queue: &mut Q,
+            enqueue: fn(&mut Q, i32),
+            deque: fn(&mut Q) -> Option<i32>,
+        ) -> Vec<i32> {
+            let mut out = Vec::new();
+            for i in 0..50 {
+                enqueue(queue, i);
+                if i % 3 == 0 {
+                    out.extend(deque(queue));
+                }
+            }
+            while let Some(val) = deque(queue) {
+                out.push(val);
+            }
+            out
+        }
+
+        let mut linked = Queue::new();
+        let mut ring = ArrayQueue::with_capacity(2);
+        assert_eq!(
+            drain_alternating(&mut linked, Queue::enqueue, Queue::deque),
+            drain_alternating(&mut ring, ArrayQueue::enqueue, ArrayQueue::deque),
+        );
+    }
+
+    #[test]
+    fn matches_vecdeque() {
+        let mut rng = rand::thread_rng();
+        let mut queue = ArrayQueue::new();
+        let mut model = VecDeque::new();
+        for step in 0..5000 {
+            match rng.gen_range(0..4) {
+                0 | 1 => {
+                    queue.enqueue(step);
+                    model.push_back(step);
+                }
+                2 => assert_eq!(queue.deque(), model.pop_front()),
+                _ => {
+                    for val in queue.iter_mut() {
+                        *val += 1;
+                    }
+                    for val in model.iter_mut() {
+                        *val += 1;
+                    }
+                }
+            }
+            assert_eq!(queue.peek(), model.front());
+            assert!(queue.iter().eq(model.iter()));
+            assert_eq!(queue.iter().len(), model.len());
+        }
+        queue.clear();
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,48 @@
+use std::{env, process::ExitCode};
+
+use last_algo_course::bisect::{Bisect, CommandOracle, Oracle, Outcome};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 || args.len() > 4 {
+        eprintln!("usage: {} <items-file> <command> [state-file]", args[0]);
+        return ExitCode::from(2);
+    }
+
+    let result = Bisect::read_items(&args[1]).and_then(|items| {
+        let mut bisect = match args.get(3) {
+            Some(state_file) => Bisect::with_state_file(items, state_file)?,
+            None => Bisect::new(items),
+        };
+        let mut oracle = CommandOracle::new(&args[2]);
+        let mut judge = |item: &str| {
+            let verdict = oracle.judge(item)?;
+            println!("{item}: {verdict:?}");
+            Ok(verdict)
+        };
+        let outcome = bisect.run(&mut judge)?;
+        Ok((bisect, outcome))
+    });
+
+    match result {
+        Ok((bisect, Outcome::FirstBad(idx))) => {
+            println!("first bad item: {}", bisect.items()[idx]);
+            ExitCode::SUCCESS
+        }
+        Ok((_, Outcome::NoBad)) => {
+            println!("no bad item found");
+            ExitCode::SUCCESS
+        }
+        Ok((bisect, Outcome::Ambiguous(candidates))) => {
+            println!("first bad item could be any of:");
+            for idx in candidates {
+                println!("  {}", bisect.items()[idx]);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("bisect failed: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
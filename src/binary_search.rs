@@ -1,15 +1,117 @@
+use std::{collections::BTreeSet, ops::Range};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Probes(usize);
+
+impl Probes {
+    pub fn new() -> Probes {
+        Probes(0)
+    }
+
+    pub fn record(&mut self) {
+        self.0 += 1;
+    }
+
+    pub fn count(&self) -> usize {
+        self.0
+    }
+}
+
 pub fn binary_search(haystack: &[u32], needle: u32) -> bool {
+    binary_search_probed(haystack, needle, &mut Probes::new())
+}
+
+pub fn binary_search_probed(haystack: &[u32], needle: u32, probes: &mut Probes) -> bool {
     if haystack.is_empty() {
         return false;
     }
     let mid = haystack.len() / 2;
+    probes.record();
     if haystack[mid] == needle {
         return true;
     }
     if haystack[mid] > needle {
-        return binary_search(&haystack[..mid], needle);
+        return binary_search_probed(&haystack[..mid], needle, probes);
     }
-    binary_search(&haystack[mid + 1..], needle)
+    binary_search_probed(&haystack[mid + 1..], needle, probes)
+}
+
+// Binary search for the first index where a predicate flips from false to true, for when the
+// predicate is expensive or can fail to answer. `lo..hi` are the indices that might still be
+// the first true one, and `hi` itself (unless it is the end of the range) is known to be true.
+// A probe that cannot answer is skipped, and the next probe goes to the nearest untested
+// neighbour of the midpoint instead.
+#[derive(Debug, Clone)]
+pub struct PartitionSearch {
+    lo: usize,
+    hi: usize,
+    skipped: BTreeSet<usize>,
+}
+
+impl PartitionSearch {
+    pub fn new(range: Range<usize>) -> PartitionSearch {
+        PartitionSearch {
+            lo: range.start,
+            hi: range.end.max(range.start),
+            skipped: BTreeSet::new(),
+        }
+    }
+
+    pub fn next_probe(&self) -> Option<usize> {
+        let (lo, hi) = (self.lo, self.hi);
+        let mid = lo + (hi - lo) / 2;
+        (0..hi - lo)
+            .flat_map(|offset| [mid.checked_add(offset), mid.checked_sub(offset + 1)])
+            .flatten()
+            .find(|&i| i >= lo && i < hi && !self.skipped.contains(&i))
+    }
+
+    // Records the predicate at `index`: `Some(true)` if it holds, `None` if it could not be
+    // told. Returns false, leaving the search as it was, if that contradicts earlier answers.
+    pub fn record(&mut self, index: usize, answer: Option<bool>) -> bool {
+        match answer {
+            Some(false) if index >= self.hi => return false,
+            Some(true) if index < self.lo => return false,
+            Some(false) => self.lo = self.lo.max(index + 1),
+            Some(true) => self.hi = self.hi.min(index),
+            None => {
+                self.skipped.insert(index);
+            }
+        }
+        true
+    }
+
+    pub fn candidates(&self) -> Range<usize> {
+        self.lo..self.hi
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next_probe().is_none()
+    }
+}
+
+// Runs a `PartitionSearch` over `range` to the end and returns what is left of the candidates:
+// an empty `p..p` when the first true index is `p` (`range.end` if there is none), or the
+// indices that skipped probes left undecided.
+pub fn partition_point_probed<F>(
+    range: Range<usize>,
+    mut pred: F,
+    probes: &mut Probes,
+) -> Range<usize>
+where
+    F: FnMut(usize) -> Option<bool>,
+{
+    let mut search = PartitionSearch::new(range);
+    while let Some(index) = search.next_probe() {
+        probes.record();
+        search.record(index, pred(index));
+    }
+    search.candidates()
+}
+
+pub fn lower_bound<T: Ord>(haystack: &[T], needle: &T) -> usize {
+    let pred = |i: usize| Some(haystack[i] >= *needle);
+    partition_point_probed(0..haystack.len(), pred, &mut Probes::new()).start
 }
 
 #[cfg(test)]
@@ -26,4 +128,57 @@ mod tests {
         assert!(binary_search(&foo, 1));
         assert!(!binary_search(&foo, 0));
     }
+
+    #[test]
+    fn counts_probes() {
+        let foo: Vec<u32> = (0..1024).collect();
+        let mut probes = Probes::new();
+        assert!(binary_search_probed(&foo, 512, &mut probes));
+        assert_eq!(probes.count(), 1);
+
+        let mut probes = Probes::new();
+        assert!(!binary_search_probed(&foo, 5000, &mut probes));
+        assert_eq!(probes.count(), 10);
+    }
+
+    #[test]
+    fn lower_bound_works() {
+        let foo = vec![1, 3, 3, 3, 69, 420];
+        assert_eq!(lower_bound(&foo, &0), 0);
+        assert_eq!(lower_bound(&foo, &1), 0);
+        assert_eq!(lower_bound(&foo, &3), 1);
+        assert_eq!(lower_bound(&foo, &4), 4);
+        assert_eq!(lower_bound(&foo, &420), 5);
+        assert_eq!(lower_bound(&foo, &421), 6);
+        assert_eq!(lower_bound(&[] as &[i32], &1), 0);
+    }
+
+    #[test]
+    fn partition_point_with_skips() {
+        let mut probes = Probes::new();
+        let found = partition_point_probed(0..1024, |i| Some(i >= 700), &mut probes);
+        assert_eq!(found, 700..700);
+        assert_eq!(probes.count(), 10);
+
+        let skip_some = |i: usize| (!(300..310).contains(&i)).then_some(i >= 305);
+        let found = partition_point_probed(0..1024, skip_some, &mut Probes::new());
+        assert_eq!(found, 300..310);
+        let found = partition_point_probed(
+            0..1024,
+            |i| (i != 512).then_some(i >= 600),
+            &mut Probes::new(),
+        );
+        assert_eq!(found, 600..600);
+        assert_eq!(
+            partition_point_probed(5..5, |_| Some(true), &mut Probes::new()),
+            5..5
+        );
+
+        let mut search = PartitionSearch::new(0..10);
+        assert!(search.record(6, Some(true)));
+        assert!(!search.record(8, Some(false)));
+        assert!(search.record(2, None));
+        assert_eq!(search.candidates(), 0..6);
+        assert_eq!(search.next_probe(), Some(3));
+    }
 }
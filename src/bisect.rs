@@ -0,0 +1,344 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    process::Command,
+};
+
+use crate::binary_search::PartitionSearch;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Good,
+    Bad,
+    Skip,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    // The item at this index is bad and every item before it is good.
+    FirstBad(usize),
+    // No item is bad: the last one was marked good (or implied good), or there are no items.
+    NoBad,
+    // Skipped items hide where good turns bad. The first bad item is one of these indices,
+    // the last of which is the earliest known bad item, or just the last item if none was
+    // found bad.
+    Ambiguous(Vec<usize>),
+}
+
+pub trait Oracle {
+    fn judge(&mut self, item: &str) -> io::Result<Verdict>;
+}
+
+impl<F: FnMut(&str) -> io::Result<Verdict>> Oracle for F {
+    fn judge(&mut self, item: &str) -> io::Result<Verdict> {
+        self(item)
+    }
+}
+
+// Runs `sh -c <command> bisect <item>`, so the item is available as `$1` (and as
+// `$BISECT_ITEM`). Exit codes follow `git bisect run`: 0 is good, 125 is skip, any other
+// code below 128 is bad, and anything else aborts the bisection.
+pub struct CommandOracle {
+    command: String,
+}
+
+impl CommandOracle {
+    pub fn new(command: impl Into<String>) -> CommandOracle {
+        CommandOracle {
+            command: command.into(),
+        }
+    }
+}
+
+impl Oracle for CommandOracle {
+    fn judge(&mut self, item: &str) -> io::Result<Verdict> {
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .arg("bisect")
+            .arg(item)
+            .env("BISECT_ITEM", item)
+            .status()?;
+        match status.code() {
+            Some(0) => Ok(Verdict::Good),
+            Some(125) => Ok(Verdict::Skip),
+            Some(1..=127) => Ok(Verdict::Bad),
+            _ => Err(io::Error::other(format!(
+                "command {status} while testing {item}, aborting"
+            ))),
+        }
+    }
+}
+
+pub struct Bisect {
+    items: Vec<String>,
+    // Searches for the first index where "is bad" holds.
+    search: PartitionSearch,
+    log: Option<File>,
+}
+
+impl Bisect {
+    pub fn new(items: Vec<String>) -> Bisect {
+        Bisect {
+            search: PartitionSearch::new(0..items.len()),
+            items,
+            log: None,
+        }
+    }
+
+    pub fn with_state_file(items: Vec<String>, path: impl AsRef<Path>) -> io::Result<Bisect> {
+        let path = path.as_ref();
+        let mut bisect = Bisect::new(items);
+        if path.exists() {
+            let index: HashMap<&str, usize> = bisect
+                .items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| (item.as_str(), i))
+                .collect();
+            let mut marks = Vec::new();
+            for line in BufReader::new(File::open(path)?).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let (verdict, item) = line.split_once(' ').ok_or_else(|| invalid_state(&line))?;
+                let verdict = match verdict {
+                    "good" => Verdict::Good,
+                    "bad" => Verdict::Bad,
+                    "skip" => Verdict::Skip,
+                    _ => return Err(invalid_state(&line)),
+                };
+                let idx = *index.get(item).ok_or_else(|| invalid_state(&line))?;
+                marks.push((idx, verdict));
+            }
+            for (idx, verdict) in marks {
+                bisect.mark(idx, verdict)?;
+            }
+        }
+        bisect.log = Some(OpenOptions::new().create(true).append(true).open(path)?);
+        Ok(bisect)
+    }
+
+    pub fn read_items(path: impl AsRef<Path>) -> io::Result<Vec<String>> {
+        Ok(fs::read_to_string(path)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect())
+    }
+
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    pub fn mark(&mut self, index: usize, verdict: Verdict) -> io::Result<()> {
+        if index >= self.items.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("index {index} out of range for {} items", self.items.len()),
+            ));
+        }
+        let is_bad = match verdict {
+            Verdict::Good => Some(false),
+            Verdict::Bad => Some(true),
+            Verdict::Skip => None,
+        };
+        if !self.search.record(index, is_bad) {
+            return Err(self.inconsistent(index, verdict));
+        }
+        if let Some(log) = self.log.as_mut() {
+            let verdict = match verdict {
+                Verdict::Good => "good",
+                Verdict::Bad => "bad",
+                Verdict::Skip => "skip",
+            };
+            writeln!(log, "{verdict} {}", self.items[index])?;
+            log.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn next_probe(&self) -> Option<usize> {
+        self.search.next_probe()
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        if !self.search.is_done() {
+            return None;
+        }
+        let candidates = self.search.candidates();
+        Some(if !candidates.is_empty() {
+            Outcome::Ambiguous(
+                (candidates.start..=candidates.end.min(self.items.len() - 1)).collect(),
+            )
+        } else if candidates.end < self.items.len() {
+            Outcome::FirstBad(candidates.end)
+        } else {
+            Outcome::NoBad
+        })
+    }
+
+    pub fn run(&mut self, oracle: &mut impl Oracle) -> io::Result<Outcome> {
+        while let Some(idx) = self.next_probe() {
+            let verdict = oracle.judge(&self.items[idx])?;
+            self.mark(idx, verdict)?;
+        }
+        Ok(self.outcome().expect("no probes left"))
+    }
+
+    fn inconsistent(&self, index: usize, verdict: Verdict) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "marking {} as {verdict:?} contradicts earlier marks",
+                self.items[index]
+            ),
+        )
+    }
+}
+
+fn invalid_state(line: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid bisect state line: {line}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered(n: usize) -> Vec<String> {
+        (0..n).map(|i| i.to_string()).collect()
+    }
+
+    fn threshold(first_bad: usize) -> impl FnMut(&str) -> io::Result<Verdict> {
+        move |item: &str| {
+            Ok(if item.parse::<usize>().unwrap() < first_bad {
+                Verdict::Good
+            } else {
+                Verdict::Bad
+            })
+        }
+    }
+
+    fn state_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("bisect-{}-{name}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn it_works() {
+        for first_bad in 0..40 {
+            let mut probes = 0;
+            let mut oracle = threshold(first_bad);
+            let mut counting = |item: &str| {
+                probes += 1;
+                oracle(item)
+            };
+            let mut bisect = Bisect::new(numbered(40));
+            assert_eq!(
+                bisect.run(&mut counting).unwrap(),
+                Outcome::FirstBad(first_bad)
+            );
+            assert!(probes <= 6);
+        }
+
+        let mut bisect = Bisect::new(numbered(40));
+        assert_eq!(bisect.run(&mut threshold(40)).unwrap(), Outcome::NoBad);
+        let mut bisect = Bisect::new(Vec::new());
+        assert_eq!(bisect.run(&mut threshold(0)).unwrap(), Outcome::NoBad);
+    }
+
+    #[test]
+    fn skips() {
+        let mut oracle = threshold(17);
+        let mut skipping = |item: &str| match item {
+            "10" | "18" | "20" => Ok(Verdict::Skip),
+            _ => oracle(item),
+        };
+        let mut bisect = Bisect::new(numbered(40));
+        assert_eq!(bisect.run(&mut skipping).unwrap(), Outcome::FirstBad(17));
+
+        let mut oracle = threshold(16);
+        let mut skipping = |item: &str| match item {
+            "15" | "16" => Ok(Verdict::Skip),
+            _ => oracle(item),
+        };
+        let mut bisect = Bisect::new(numbered(40));
+        assert_eq!(
+            bisect.run(&mut skipping).unwrap(),
+            Outcome::Ambiguous(vec![15, 16, 17])
+        );
+    }
+
+    #[test]
+    fn rejects_inconsistent_marks() {
+        let mut bisect = Bisect::new(numbered(10));
+        bisect.mark(3, Verdict::Bad).unwrap();
+        assert!(bisect.mark(5, Verdict::Good).is_err());
+        assert!(bisect.mark(10, Verdict::Skip).is_err());
+        bisect.mark(1, Verdict::Good).unwrap();
+        assert_eq!(bisect.next_probe(), Some(2));
+    }
+
+    #[test]
+    fn resumes_from_state_file() {
+        let path = state_path("resume");
+        let mut calls = 0;
+        let mut oracle = threshold(70);
+        let mut flaky = |item: &str| {
+            calls += 1;
+            if calls == 4 {
+                return Err(io::Error::other("interrupted"));
+            }
+            oracle(item)
+        };
+        let mut bisect = Bisect::with_state_file(numbered(100), &path).unwrap();
+        assert!(bisect.run(&mut flaky).is_err());
+
+        let mut resumed_probes = 0;
+        let mut oracle = threshold(70);
+        let mut counting = |item: &str| {
+            resumed_probes += 1;
+            oracle(item)
+        };
+        let mut bisect = Bisect::with_state_file(numbered(100), &path).unwrap();
+        assert_eq!(bisect.run(&mut counting).unwrap(), Outcome::FirstBad(70));
+        assert!(resumed_probes <= 5);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().lines().count(),
+            3 + resumed_probes
+        );
+
+        let bisect = Bisect::with_state_file(numbered(100), &path).unwrap();
+        assert_eq!(bisect.outcome(), Some(Outcome::FirstBad(70)));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn command_oracle() {
+        let items: Vec<String> = ["v1.0", "v1.1", "v1.2", "v2.0", "v2.1", "v2.2", "v3.0"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut oracle = CommandOracle::new(
+            r#"case "$1" in v1.*) exit 0 ;; v2.0) exit 125 ;; v2.*) exit 0 ;; *) exit 1 ;; esac"#,
+        );
+        let mut bisect = Bisect::new(items.clone());
+        assert_eq!(bisect.run(&mut oracle).unwrap(), Outcome::FirstBad(6));
+
+        let mut oracle = CommandOracle::new(r#"test "$BISECT_ITEM" \< v2.1"#);
+        let mut bisect = Bisect::new(items.clone());
+        assert_eq!(bisect.run(&mut oracle).unwrap(), Outcome::FirstBad(4));
+
+        let mut oracle = CommandOracle::new("exit 200");
+        let mut bisect = Bisect::new(items);
+        assert!(bisect.run(&mut oracle).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-fn bubble_sort(arr: &mut Vec<i32>) {
+fn bubble_sort(arr: &mut [i32]) {
     let length = arr.len();
     for i in 0..length {
         for j in 0..length - i - 1 {
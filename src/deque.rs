@@ -0,0 +1,553 @@
+use std::{
+    fmt,
+    iter::FusedIterator,
+    mem::{self, MaybeUninit},
+    ops::{Index, IndexMut},
+    slice,
+};
+
+// The `len` initialised elements start at `head` and wrap around the end of `buf`; every
+// other slot is uninitialised. Indices used below are logical (0 is the front) unless they
+// go through `physical`.
+pub struct Deque<T> {
+    buf: Box<[MaybeUninit<T>]>,
+    head: usize,
+    len: usize,
+}
+
+pub struct DequeIter<'a, T> {
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+pub struct DequeIterMut<'a, T> {
+    front: slice::IterMut<'a, T>,
+    back: slice::IterMut<'a, T>,
+}
+
+pub struct IntoDequeIter<T> {
+    deque: Deque<T>,
+}
+
+fn uninit_buf<T>(capacity: usize) -> Box<[MaybeUninit<T>]> {
+    (0..capacity).map(|_| MaybeUninit::uninit()).collect()
+}
+
+impl<T> Deque<T> {
+    pub fn new() -> Deque<T> {
+        Deque::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Deque<T> {
+        Deque {
+            buf: uninit_buf(capacity),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    fn physical(&self, index: usize) -> usize {
+        let slot = self.head + index;
+        if slot >= self.buf.len() {
+            slot - self.buf.len()
+        } else {
+            slot
+        }
+    }
+
+    fn grow_if_full(&mut self) {
+        if self.len < self.buf.len() {
+            return;
+        }
+        let mut buf = uninit_buf((self.buf.len() * 2).max(4));
+        for (index, slot) in buf.iter_mut().enumerate().take(self.len) {
+            let from = self.physical(index);
+            *slot = mem::replace(&mut self.buf[from], MaybeUninit::uninit());
+        }
+        self.buf = buf;
+        self.head = 0;
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        self.grow_if_full();
+        let slot = self.physical(self.len);
+        self.buf[slot].write(elem);
+        self.len += 1;
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        self.grow_if_full();
+        self.head = self.physical(self.buf.len() - 1);
+        self.buf[self.head].write(elem);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let elem = unsafe { self.buf[self.head].assume_init_read() };
+        self.head = self.physical(1);
+        self.len -= 1;
+        Some(elem)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let slot = self.physical(self.len);
+        unsafe { Some(self.buf[slot].assume_init_read()) }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        unsafe { Some(self.buf[self.physical(index)].assume_init_ref()) }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let slot = self.physical(index);
+        unsafe { Some(self.buf[slot].assume_init_mut()) }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.wrapping_sub(1))
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.wrapping_sub(1))
+    }
+
+    // Moves the first `n` elements to the back, shifting whichever side is shorter.
+    pub fn rotate_left(&mut self, n: usize) {
+        assert!(n <= self.len, "Cannot rotate by more than the length");
+        if self.len == self.buf.len() {
+            self.head = self.physical(n);
+        } else if n <= self.len / 2 {
+            for _ in 0..n {
+                let elem = self.pop_front().unwrap();
+                self.push_back(elem);
+            }
+        } else {
+            self.rotate_right(self.len - n);
+        }
+    }
+
+    pub fn rotate_right(&mut self, n: usize) {
+        assert!(n <= self.len, "Cannot rotate by more than the length");
+        if self.len == self.buf.len() {
+            self.head = self.physical(self.len - n);
+        } else if n <= self.len / 2 {
+            for _ in 0..n {
+                let elem = self.pop_back().unwrap();
+                self.push_front(elem);
+            }
+        } else {
+            self.rotate_left(self.len - n);
+        }
+    }
+
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let first = self.len.min(self.buf.len() - self.head);
+        unsafe {
+            (
+                slice::from_raw_parts(self.buf[self.head..].as_ptr().cast(), first),
+                slice::from_raw_parts(self.buf.as_ptr().cast(), self.len - first),
+            )
+        }
+    }
+
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let first = self.len.min(self.buf.len() - self.head);
+        let (before, after) = self.buf.split_at_mut(self.head);
+        unsafe {
+            (
+                slice::from_raw_parts_mut(after.as_mut_ptr().cast(), first),
+                slice::from_raw_parts_mut(before.as_mut_ptr().cast(), self.len - first),
+            )
+        }
+    }
+
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if self.head + self.len > self.buf.len() {
+            self.buf.rotate_left(self.head);
+            self.head = 0;
+        }
+        self.as_mut_slices().0
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_back().is_some() {}
+        self.head = 0;
+    }
+
+    pub fn iter(&self) -> DequeIter<'_, T> {
+        let (front, back) = self.as_slices();
+        DequeIter {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> DequeIterMut<'_, T> {
+        let (front, back) = self.as_mut_slices();
+        DequeIterMut {
+            front: front.iter_mut(),
+            back: back.iter_mut(),
+        }
+    }
+
+    pub fn into_iter(self) -> IntoDequeIter<T> {
+        IntoDequeIter { deque: self }
+    }
+}
+
+impl<T> Drop for Deque<T> {
+    fn drop(&mut self) {
+        // Keeps dropping the remaining elements if dropping one of them panics.
+        struct DropGuard<'a, T>(&'a mut Deque<T>);
+
+        impl<'a, T> Drop for DropGuard<'a, T> {
+            fn drop(&mut self) {
+                while self.0.pop_front().is_some() {}
+            }
+        }
+
+        let guard = DropGuard(self);
+        while guard.0.pop_front().is_some() {}
+        mem::forget(guard);
+    }
+}
+
+impl<T> Index<usize> for Deque<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("Out of bounds access")
+    }
+}
+
+impl<T> IndexMut<usize> for Deque<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.get_mut(index).expect("Out of bounds access")
+    }
+}
+
+impl<T> Default for Deque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for Deque<T> {
+    fn clone(&self) -> Self {
+        let mut deque = Deque::with_capacity(self.len);
+        deque.extend(self.iter().cloned());
+        deque
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Deque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Deque<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for Deque<T> {}
+
+impl<T> FromIterator<T> for Deque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut deque = Deque::new();
+        deque.extend(iter);
+        deque
+    }
+}
+
+impl<T> Extend<T> for Deque<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a> Extend<&'a T> for Deque<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Deque<T> {
+    type Item = &'a T;
+
+    type IntoIter = DequeIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Clone for DequeIter<'a, T> {
+    fn clone(&self) -> Self {
+        DequeIter {
+            front: self.front.clone(),
+            back: self.back.clone(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for DequeIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for DequeIter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Deque<T> {
+    type IntoIter = DequeIterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for DequeIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for DequeIterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<T> IntoIterator for Deque<T> {
+    type IntoIter = IntoDequeIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_iter()
+    }
+}
+
+impl<T> Iterator for IntoDequeIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.deque.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.deque.len, Some(self.deque.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoDequeIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.deque.pop_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for DequeIter<'a, T> {}
+
+impl<'a, T> ExactSizeIterator for DequeIterMut<'a, T> {}
+
+impl<T> ExactSizeIterator for IntoDequeIter<T> {}
+
+impl<'a, T> FusedIterator for DequeIter<'a, T> {}
+
+impl<'a, T> FusedIterator for DequeIterMut<'a, T> {}
+
+impl<T> FusedIterator for IntoDequeIter<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::DropCounter;
+    use rand::Rng;
+    use std::{cell::Cell, collections::VecDeque};
+
+    #[test]
+    fn it_works() {
+        let mut deque = Deque::new();
+        assert_eq!(deque.pop_front(), None);
+        assert_eq!(deque.pop_back(), None);
+        deque.push_back(2);
+        deque.push_back(3);
+        deque.push_front(1);
+        deque.push_front(0);
+        deque.push_back(4);
+        assert_eq!(deque.len(), 5);
+        assert_eq!(deque[0], 0);
+        deque[4] = 40;
+        assert_eq!(deque.back(), Some(&40));
+        assert_eq!(deque.get(5), None);
+        assert_eq!(format!("{:?}", deque), "[0, 1, 2, 3, 40]");
+
+        assert_eq!(deque.pop_front(), Some(0));
+        assert_eq!(deque.pop_back(), Some(40));
+        *deque.front_mut().unwrap() = 10;
+        *deque.back_mut().unwrap() = 30;
+        assert_eq!(
+            deque.clone().into_iter().rev().collect::<Vec<_>>(),
+            vec![30, 2, 10]
+        );
+        deque.clear();
+        assert!(deque.is_empty());
+        assert_eq!(deque.front(), None);
+        assert_eq!(deque.back(), None);
+    }
+
+    #[test]
+    fn slices_and_rotation() {
+        let mut deque = Deque::with_capacity(8);
+        for i in 0..4 {
+            deque.push_back(i);
+        }
+        for i in 1..=3 {
+            deque.push_front(-i);
+        }
+        assert_eq!(deque.capacity(), 8);
+        assert_eq!(deque.as_slices(), (&[-3, -2, -1][..], &[0, 1, 2, 3][..]));
+
+        deque.rotate_left(2);
+        assert_eq!(
+            deque.iter().copied().collect::<Vec<_>>(),
+            vec![-1, 0, 1, 2, 3, -3, -2]
+        );
+        deque.rotate_right(5);
+        assert_eq!(
+            deque.iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, -3, -2, -1, 0]
+        );
+
+        let slice = deque.make_contiguous();
+        slice.sort();
+        assert_eq!(slice, &[-3, -2, -1, 0, 1, 2, 3]);
+        assert!(deque.as_slices().1.is_empty());
+        for elem in deque.as_mut_slices().0 {
+            *elem *= 2;
+        }
+        assert_eq!(deque.iter().next_back(), Some(&6));
+    }
+
+    #[test]
+    fn matches_vecdeque() {
+        let mut rng = rand::thread_rng();
+        let mut deque = Deque::new();
+        let mut model = VecDeque::new();
+        for step in 0..5000 {
+            match rng.gen_range(0..8) {
+                0 => {
+                    deque.push_front(step);
+                    model.push_front(step);
+                }
+                1 | 2 => {
+                    deque.push_back(step);
+                    model.push_back(step);
+                }
+                3 => assert_eq!(deque.pop_front(), model.pop_front()),
+                4 => assert_eq!(deque.pop_back(), model.pop_back()),
+                5 => {
+                    let n = rng.gen_range(0..=model.len());
+                    deque.rotate_left(n);
+                    model.rotate_left(n);
+                }
+                6 => {
+                    let n = rng.gen_range(0..=model.len());
+                    deque.rotate_right(n);
+                    model.rotate_right(n);
+                }
+                _ => assert_eq!(deque.make_contiguous(), model.make_contiguous()),
+            }
+            let (front, back) = deque.as_slices();
+            assert_eq!([front, back].concat(), Vec::from(model.clone()));
+            assert!(deque.iter().rev().eq(model.iter().rev()));
+            if !model.is_empty() {
+                let index = rng.gen_range(0..model.len());
+                assert_eq!(deque[index], model[index]);
+            }
+        }
+    }
+
+    #[test]
+    fn drops_every_element() {
+        let drops = Cell::new(0);
+        let mut deque = Deque::new();
+        for i in 0..10 {
+            if i % 2 == 0 {
+                deque.push_front(DropCounter(&drops));
+            } else {
+                deque.push_back(DropCounter(&drops));
+            }
+        }
+        drop(deque.pop_back());
+        assert_eq!(drops.get(), 1);
+        deque.rotate_left(3);
+        deque.make_contiguous();
+        assert_eq!(drops.get(), 1);
+        drop(deque);
+        assert_eq!(drops.get(), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of bounds access")]
+    fn index_out_of_bounds() {
+        let deque: Deque<i32> = (0..3).collect();
+        let _ = deque[3];
+    }
+}
@@ -1,12 +1,28 @@
 #![allow(dead_code)]
 
+mod aho_corasick;
+mod arena_list;
+mod array_queue;
 mod binary_search;
+pub mod bisect;
 mod bubble_sort;
+mod deque;
 mod linear_search;
 mod linked_list;
+mod lru_cache;
 mod path_finding;
+mod persistent_list;
+mod persistent_queue;
 mod queue;
 mod quick_sort;
+mod set_operations;
+mod skip_list;
 mod stack;
+mod substring_search;
+pub mod sync;
+#[cfg(test)]
+mod test_fixtures;
+mod timer_wheel;
 mod tree;
 mod two_crystal_balls;
+mod unimodal_search;
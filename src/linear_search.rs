@@ -1,12 +1,62 @@
-pub fn linear_search(haystack: &Vec<u32>, needle: u32) -> bool {
-    for i in 0..haystack.len() {
-        if haystack[i] == needle {
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+const CANCEL_CHECK_INTERVAL: usize = 4096;
+
+pub fn linear_search(haystack: &[u32], needle: u32) -> bool {
+    for &item in haystack {
+        if item == needle {
             return true;
         }
     }
     false
 }
 
+pub fn parallel_linear_search<T: PartialEq + Sync>(haystack: &[T], needle: &T) -> Option<usize> {
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+    parallel_linear_search_with(haystack, needle, workers)
+}
+
+pub fn parallel_linear_search_with<T: PartialEq + Sync>(
+    haystack: &[T],
+    needle: &T,
+    workers: usize,
+) -> Option<usize> {
+    if haystack.is_empty() {
+        return None;
+    }
+    let chunk_size = haystack.len().div_ceil(workers.max(1));
+    let found = AtomicUsize::new(usize::MAX);
+
+    thread::scope(|scope| {
+        for (chunk_idx, chunk) in haystack.chunks(chunk_size).enumerate() {
+            let found = &found;
+            scope.spawn(move || {
+                let start = chunk_idx * chunk_size;
+                for (offset, item) in chunk.iter().enumerate() {
+                    // Chunks are scanned in order, so once an earlier chunk has a match
+                    // nothing in this one can be the lowest index.
+                    if offset % CANCEL_CHECK_INTERVAL == 0 && found.load(Ordering::Relaxed) < start
+                    {
+                        return;
+                    }
+                    if item == needle {
+                        found.fetch_min(start + offset, Ordering::Relaxed);
+                        return;
+                    }
+                }
+            });
+        }
+    });
+
+    match found.into_inner() {
+        usize::MAX => None,
+        idx => Some(idx),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -21,4 +71,24 @@ mod tests {
         assert!(linear_search(&foo, 1));
         assert!(!linear_search(&foo, 0));
     }
+
+    #[test]
+    fn parallel_finds_lowest_index() {
+        let mut haystack = vec![0u64; 1_000_000];
+        for idx in [999_999, 750_000, 250_001, 250_000, 31] {
+            haystack[idx] = 7;
+        }
+        for workers in [1, 2, 3, 4, 8, 64] {
+            assert_eq!(
+                parallel_linear_search_with(&haystack, &7, workers),
+                Some(31)
+            );
+            assert_eq!(parallel_linear_search_with(&haystack, &8, workers), None);
+        }
+        haystack[31] = 0;
+        assert_eq!(parallel_linear_search(&haystack, &7), Some(250_000));
+        assert_eq!(parallel_linear_search_with(&haystack[..5], &7, 0), None);
+        assert_eq!(parallel_linear_search_with(&[] as &[u64], &7, 4), None);
+        assert_eq!(parallel_linear_search_with(&[7u64], &7, 16), Some(0));
+    }
 }
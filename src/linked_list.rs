@@ -1,20 +1,58 @@
-use std::{marker::PhantomData, ptr::NonNull};
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
+    marker::PhantomData,
+    mem,
+    ops::{Bound, RangeBounds},
+    ptr::NonNull,
+    sync::atomic::{AtomicU64, Ordering as AtomicOrdering},
+};
 
 type Link<T> = Option<NonNull<Node<T>>>;
 
+const NO_SLOT: u32 = u32::MAX;
+
+static NEXT_LIST_ID: AtomicU64 = AtomicU64::new(1);
+
 struct Node<T> {
     elem: T,
     front: Link<T>,
     back: Link<T>,
+    slot: u32,
+}
+
+struct Slot<T> {
+    node: Link<T>,
+    generation: u64,
 }
 
 pub struct DoublyLinkedList<T> {
     front: Link<T>,
     back: Link<T>,
-    pub len: usize,
+    len: usize,
+    id: u64,
+    slots: Vec<Slot<T>>,
+    free_slots: Vec<u32>,
     _boo: PhantomData<T>,
 }
 
+// Handles are resolved through a slot table owned by the list: removing a node bumps its
+// slot's generation, so an old handle can never reach freed (or reused) memory. Nodes that
+// move to another list (split, splice, append, merge) give up their handles.
+//
+// A node's `slot` only counts if the list's table points back at that node, so a node
+// arriving from another list can keep its stale `slot`. Moving a whole list therefore just
+// throws its table away and stays O(1). Splitting off part of a list that has live handles
+// walks the part that moves to retire its handles, so that costs O(moved) instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeHandle {
+    list: u64,
+    slot: u32,
+    generation: u64,
+}
+
 pub struct DoublyLinkedListIter<'a, T> {
     front: Link<T>,
     back: Link<T>,
@@ -33,12 +71,38 @@ pub struct IntoDoublyLinkedListIter<T> {
     list: DoublyLinkedList<T>,
 }
 
+pub struct ExtractIf<'a, T, F> {
+    list: &'a mut DoublyLinkedList<T>,
+    current: Link<T>,
+    pred: F,
+}
+
+pub struct Drain<'a, T> {
+    list: DoublyLinkedList<T>,
+    _boo: PhantomData<&'a mut DoublyLinkedList<T>>,
+}
+
+pub struct Cursor<'a, T> {
+    list: &'a DoublyLinkedList<T>,
+    current: Link<T>,
+    index: usize,
+}
+
+pub struct CursorMut<'a, T> {
+    list: &'a mut DoublyLinkedList<T>,
+    current: Link<T>,
+    index: usize,
+}
+
 impl<T> DoublyLinkedList<T> {
     pub fn new() -> DoublyLinkedList<T> {
         DoublyLinkedList {
             front: None,
             back: None,
             len: 0,
+            id: 0,
+            slots: Vec::new(),
+            free_slots: Vec::new(),
             _boo: PhantomData,
         }
     }
@@ -48,6 +112,7 @@ impl<T> DoublyLinkedList<T> {
             let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
                 front: None,
                 back: None,
+                slot: NO_SLOT,
                 elem,
             })));
             if let Some(old) = self.front {
@@ -64,6 +129,7 @@ impl<T> DoublyLinkedList<T> {
     pub fn pop_front(&mut self) -> Option<T> {
         unsafe {
             self.front.map(|node| {
+                self.release_handle(node);
                 let boxed_node = Box::from_raw(node.as_ptr());
                 let result = boxed_node.elem;
 
@@ -85,6 +151,7 @@ impl<T> DoublyLinkedList<T> {
             let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
                 front: None,
                 back: None,
+                slot: NO_SLOT,
                 elem,
             })));
             if let Some(old) = self.back {
@@ -101,6 +168,7 @@ impl<T> DoublyLinkedList<T> {
     pub fn pop_back(&mut self) -> Option<T> {
         unsafe {
             self.back.map(|node| {
+                self.release_handle(node);
                 let boxed_node = Box::from_raw(node.as_ptr());
                 let result = boxed_node.elem;
 
@@ -121,66 +189,528 @@ impl<T> DoublyLinkedList<T> {
         self.len
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     fn get_at(&self, index: usize) -> Link<T> {
-        let mut remaining_index = index;
-        let mut curr_node = self.front;
-        while remaining_index > 0 {
-            match curr_node {
-                Some(node) => unsafe {
-                    curr_node = (*node.as_ptr()).back;
-                    remaining_index -= 1;
-                },
-                None => {
-                    return None;
+        if index >= self.len {
+            return None;
+        }
+        let mut curr_node = self.front?;
+        unsafe {
+            if index <= self.len / 2 {
+                for _ in 0..index {
+                    curr_node = (*curr_node.as_ptr()).back?;
+                }
+            } else {
+                curr_node = self.back?;
+                for _ in index + 1..self.len {
+                    curr_node = (*curr_node.as_ptr()).front?;
                 }
             }
         }
-        curr_node
+        Some(curr_node)
     }
 
-    pub fn remove_at(&mut self, index: usize) {
-        if let Some(node_at) = self.get_at(index) {
-            unsafe {
-                let before_node = (*node_at.as_ptr()).back;
-                let after_node = (*node_at.as_ptr()).front;
-                match (before_node, after_node) {
-                    (Some(before_node), Some(after_node)) => {
-                        (*before_node.as_ptr()).front = Some(after_node);
-                        (*after_node.as_ptr()).back = Some(before_node);
-                        self.len -= 1;
-                    }
-                    (None, Some(after_node)) => {
-                        (*after_node.as_ptr()).back = None;
-                        self.len -= 1;
-                    }
-                    (Some(before_node), None) => {
-                        (*before_node.as_ptr()).front = None;
-                        self.len -= 1;
-                    }
-                    (None, None) => {}
+    unsafe fn link_chain(
+        &mut self,
+        prev: Link<T>,
+        next: Link<T>,
+        first: NonNull<Node<T>>,
+        last: NonNull<Node<T>>,
+        count: usize,
+    ) {
+        (*first.as_ptr()).front = prev;
+        (*last.as_ptr()).back = next;
+        match prev {
+            Some(prev) => (*prev.as_ptr()).back = Some(first),
+            None => self.front = Some(first),
+        }
+        match next {
+            Some(next) => (*next.as_ptr()).front = Some(last),
+            None => self.back = Some(last),
+        }
+        self.len += count;
+    }
+
+    unsafe fn unlink_node(&mut self, node: NonNull<Node<T>>) -> Box<Node<T>> {
+        self.release_handle(node);
+        let node = Box::from_raw(node.as_ptr());
+        match node.front {
+            Some(prev) => (*prev.as_ptr()).back = node.back,
+            None => self.front = node.back,
+        }
+        match node.back {
+            Some(next) => (*next.as_ptr()).front = node.front,
+            None => self.back = node.front,
+        }
+        self.len -= 1;
+        node
+    }
+
+    // `other` and its handle table are dropped here, so its handles die with it.
+    unsafe fn splice_list(&mut self, prev: Link<T>, next: Link<T>, mut other: DoublyLinkedList<T>) {
+        if let (Some(first), Some(last)) = (other.front.take(), other.back.take()) {
+            let count = mem::replace(&mut other.len, 0);
+            self.link_chain(prev, next, first, last, count);
+        }
+    }
+
+    unsafe fn split_after_node(&mut self, at: Link<T>, count: usize) -> DoublyLinkedList<T> {
+        let first = match at {
+            Some(at) => (*at.as_ptr()).back.take(),
+            None => self.front.take(),
+        };
+        let mut split = DoublyLinkedList::new();
+        if let Some(first) = first {
+            self.release_chain_handles(Some(first));
+            (*first.as_ptr()).front = None;
+            split.front = Some(first);
+            split.back = mem::replace(&mut self.back, at);
+            split.len = count;
+            self.len -= count;
+        }
+        split
+    }
+
+    unsafe fn split_before_node(&mut self, at: Link<T>, count: usize) -> DoublyLinkedList<T> {
+        let last = match at {
+            Some(at) => (*at.as_ptr()).front.take(),
+            None => self.back.take(),
+        };
+        let mut split = DoublyLinkedList::new();
+        if let Some(last) = last {
+            (*last.as_ptr()).back = None;
+            split.back = Some(last);
+            split.front = mem::replace(&mut self.front, at);
+            self.release_chain_handles(split.front);
+            split.len = count;
+            self.len -= count;
+        }
+        split
+    }
+
+    pub fn remove_at(&mut self, index: usize) -> Option<T> {
+        let node = self.get_at(index)?;
+        unsafe { Some(self.unlink_node(node).elem) }
+    }
+
+    pub fn insert_at(&mut self, elem: T, index: usize) -> Result<(), T> {
+        if index > self.len {
+            return Err(elem);
+        }
+        unsafe {
+            let next = self.get_at(index);
+            let prev = match next {
+                Some(next) => (*next.as_ptr()).front,
+                None => self.back,
+            };
+            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                front: None,
+                back: None,
+                slot: NO_SLOT,
+                elem,
+            })));
+            self.link_chain(prev, next, new, new, 1);
+        }
+        Ok(())
+    }
+
+    pub fn append(&mut self, other: &mut DoublyLinkedList<T>) {
+        unsafe { self.splice_list(self.back, None, mem::take(other)) }
+    }
+
+    pub fn prepend(&mut self, other: &mut DoublyLinkedList<T>) {
+        unsafe { self.splice_list(None, self.front, mem::take(other)) }
+    }
+
+    pub fn split_off(&mut self, at: usize) -> DoublyLinkedList<T> {
+        assert!(at <= self.len, "Cannot split off at a nonexistent index");
+        if at == 0 {
+            // Same as moving every node out, but without walking them: the nodes go to a new
+            // list and the handles stay behind with `self`'s old table.
+            let mut split = DoublyLinkedList::new();
+            split.front = self.front.take();
+            split.back = self.back.take();
+            split.len = mem::replace(&mut self.len, 0);
+            self.forget_handles();
+            return split;
+        }
+        let count = self.len - at;
+        unsafe { self.split_after_node(self.get_at(at - 1), count) }
+    }
+
+    pub fn splice(&mut self, at: usize, other: DoublyLinkedList<T>) {
+        assert!(at <= self.len, "Cannot splice at a nonexistent index");
+        unsafe {
+            let next = self.get_at(at);
+            let prev = match next {
+                Some(next) => (*next.as_ptr()).front,
+                None => self.back,
+            };
+            self.splice_list(prev, next, other);
+        }
+    }
+
+    unsafe fn detach_front(&mut self) -> Option<NonNull<Node<T>>> {
+        let node = self.front?;
+        self.front = (*node.as_ptr()).back.take();
+        match self.front {
+            Some(next) => (*next.as_ptr()).front = None,
+            None => self.back = None,
+        }
+        self.len -= 1;
+        Some(node)
+    }
+
+    pub fn merge(&mut self, other: &mut DoublyLinkedList<T>)
+    where
+        T: Ord,
+    {
+        self.merge_by(other, T::cmp);
+    }
+
+    pub fn merge_by<F>(&mut self, other: &mut DoublyLinkedList<T>, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        unsafe {
+            other.forget_handles();
+            let mut curr = self.front;
+            while let (Some(node), Some(candidate)) = (curr, other.front) {
+                // Ties keep elements of `self` first, which makes the merge stable.
+                if compare(&(*candidate.as_ptr()).elem, &(*node.as_ptr()).elem) == Ordering::Less {
+                    let moved = other.detach_front().unwrap();
+                    self.link_chain((*node.as_ptr()).front, Some(node), moved, moved, 1);
+                } else {
+                    curr = (*node.as_ptr()).back;
+                }
+            }
+        }
+        self.append(other);
+    }
+
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        // Bottom-up merge sort using a binary counter of sorted runs: `runs[i]` is either
+        // empty or holds 2^i nodes, so 64 slots cover any list. Nodes only ever move between
+        // well-formed lists, and if `compare` panics the guard hands them all back to `self`.
+        struct SortGuard<'a, T> {
+            list: &'a mut DoublyLinkedList<T>,
+            carry: DoublyLinkedList<T>,
+            runs: [DoublyLinkedList<T>; 64],
+        }
+
+        impl<'a, T> Drop for SortGuard<'a, T> {
+            fn drop(&mut self) {
+                self.list.append(&mut self.carry);
+                for run in self.runs.iter_mut() {
+                    self.list.append(run);
+                }
+            }
+        }
+
+        if self.len < 2 {
+            return;
+        }
+        let mut guard = SortGuard {
+            list: self,
+            carry: DoublyLinkedList::new(),
+            runs: std::array::from_fn(|_| DoublyLinkedList::new()),
+        };
+        let mut filled = 0;
+        while let Some(node) = unsafe { guard.list.detach_front() } {
+            unsafe { guard.carry.link_chain(None, None, node, node, 1) };
+            let mut i = 0;
+            while i < filled && !guard.runs[i].is_empty() {
+                guard.runs[i].merge_by(&mut guard.carry, &mut compare);
+                mem::swap(&mut guard.carry, &mut guard.runs[i]);
+                i += 1;
+            }
+            mem::swap(&mut guard.carry, &mut guard.runs[i]);
+            if i == filled {
+                filled += 1;
+            }
+        }
+        for i in 1..filled {
+            let (lower, upper) = guard.runs.split_at_mut(i);
+            upper[0].merge_by(&mut lower[i - 1], &mut compare);
+        }
+        // Only relink the nodes: `self` keeps its slot table, so handles survive sorting.
+        let sorted = &mut guard.runs[filled - 1];
+        guard.list.append(sorted);
+    }
+
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        K: PartialEq,
+        F: FnMut(&mut T) -> K,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        unsafe {
+            let Some(mut kept) = self.front else {
+                return;
+            };
+            while let Some(node) = (*kept.as_ptr()).back {
+                if same_bucket(&mut (*node.as_ptr()).elem, &mut (*kept.as_ptr()).elem) {
+                    drop(self.unlink_node(node));
+                } else {
+                    kept = node;
                 }
             }
         }
     }
 
-    pub fn insert_at(&mut self, elem: T, index: usize) {
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|elem| f(elem));
+    }
+
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
         unsafe {
-            if let Some(node_at) = self.get_at(index) {
-                let before_node = (*node_at.as_ptr()).back;
-                if let Some(inner_before_node) = before_node {
-                    let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-                        front: Some(node_at),
-                        back: before_node,
-                        elem,
-                    })));
-                    (*node_at.as_ptr()).back = Some(new);
-                    (*inner_before_node.as_ptr()).front = Some(new);
-                    self.len += 1;
-                    return;
+            let mut curr = self.front;
+            while let Some(node) = curr {
+                curr = (*node.as_ptr()).back;
+                if !f(&mut (*node.as_ptr()).elem) {
+                    drop(self.unlink_node(node));
+                }
+            }
+        }
+    }
+
+    // Nothing is removed until the iterator is advanced; elements it never reaches stay put.
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        ExtractIf {
+            current: self.front,
+            list: self,
+            pred,
+        }
+    }
+
+    // The range is unlinked up front, so it is gone even if the `Drain` is dropped unread.
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, T>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+        assert!(
+            start <= end && end <= self.len,
+            "Cannot drain a range outside the list"
+        );
+        let mut drained = DoublyLinkedList::new();
+        if start < end {
+            unsafe {
+                let first = self.get_at(start).unwrap();
+                let last = self.get_at(end - 1).unwrap();
+                let prev = (*first.as_ptr()).front.take();
+                let next = (*last.as_ptr()).back.take();
+                match prev {
+                    Some(prev) => (*prev.as_ptr()).back = next,
+                    None => self.front = next,
+                }
+                match next {
+                    Some(next) => (*next.as_ptr()).front = prev,
+                    None => self.back = prev,
+                }
+                self.len -= end - start;
+                self.release_chain_handles(Some(first));
+                drained.link_chain(None, None, first, last, end - start);
+            }
+        }
+        Drain {
+            list: drained,
+            _boo: PhantomData,
+        }
+    }
+
+    unsafe fn release_handle(&mut self, node: NonNull<Node<T>>) {
+        let slot = mem::replace(&mut (*node.as_ptr()).slot, NO_SLOT);
+        let Some(entry) = self.slots.get_mut(slot as usize) else {
+            return;
+        };
+        if entry.node != Some(node) {
+            return;
+        }
+        entry.node = None;
+        entry.generation += 1;
+        self.free_slots.push(slot);
+    }
+
+    fn has_live_handles(&self) -> bool {
+        self.slots.len() != self.free_slots.len()
+    }
+
+    unsafe fn release_chain_handles(&mut self, first: Link<T>) {
+        if !self.has_live_handles() {
+            return;
+        }
+        let mut curr = first;
+        while let Some(node) = curr {
+            #[cfg(test)]
+            tests::HANDLE_WALK_STEPS.with(|steps| steps.set(steps.get() + 1));
+            self.release_handle(node);
+            curr = (*node.as_ptr()).back;
+        }
+    }
+
+    // Invalidates every handle at once by starting a fresh table under a fresh id.
+    fn forget_handles(&mut self) {
+        if self.id != 0 {
+            self.id = 0;
+            self.slots = Vec::new();
+            self.free_slots = Vec::new();
+        }
+    }
+
+    fn attach_handle(&mut self, node: NonNull<Node<T>>) -> NodeHandle {
+        if self.id == 0 {
+            self.id = NEXT_LIST_ID.fetch_add(1, AtomicOrdering::Relaxed);
+        }
+        let slot = match self.free_slots.pop() {
+            Some(slot) => slot,
+            None => {
+                let slot = u32::try_from(self.slots.len())
+                    .ok()
+                    .filter(|&slot| slot != NO_SLOT)
+                    .expect("Too many live node handles");
+                self.slots.push(Slot {
+                    node: None,
+                    generation: 0,
+                });
+                slot
+            }
+        };
+        self.slots[slot as usize].node = Some(node);
+        unsafe { (*node.as_ptr()).slot = slot };
+        NodeHandle {
+            list: self.id,
+            slot,
+            generation: self.slots[slot as usize].generation,
+        }
+    }
+
+    fn resolve(&self, handle: NodeHandle) -> Link<T> {
+        if self.id == 0 || handle.list != self.id {
+            return None;
+        }
+        let entry = self.slots.get(handle.slot as usize)?;
+        if entry.generation != handle.generation {
+            return None;
+        }
+        entry.node
+    }
+
+    pub fn push_front_handle(&mut self, elem: T) -> NodeHandle {
+        self.push_front(elem);
+        self.attach_handle(self.front.unwrap())
+    }
+
+    pub fn push_back_handle(&mut self, elem: T) -> NodeHandle {
+        self.push_back(elem);
+        self.attach_handle(self.back.unwrap())
+    }
+
+    pub fn get(&self, handle: NodeHandle) -> Option<&T> {
+        self.resolve(handle)
+            .map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    pub fn get_mut(&mut self, handle: NodeHandle) -> Option<&mut T> {
+        self.resolve(handle)
+            .map(|node| unsafe { &mut (*node.as_ptr()).elem })
+    }
+
+    pub fn remove(&mut self, handle: NodeHandle) -> Option<T> {
+        let node = self.resolve(handle)?;
+        unsafe { Some(self.unlink_node(node).elem) }
+    }
+
+    pub fn move_to_front(&mut self, handle: NodeHandle) -> bool {
+        let Some(node) = self.resolve(handle) else {
+            return false;
+        };
+        if self.front != Some(node) {
+            unsafe {
+                let prev = (*node.as_ptr()).front;
+                let next = (*node.as_ptr()).back;
+                (*prev.unwrap().as_ptr()).back = next;
+                match next {
+                    Some(next) => (*next.as_ptr()).front = prev,
+                    None => self.back = prev,
+                }
+                self.len -= 1;
+                self.link_chain(None, self.front, node, node, 1);
+            }
+        }
+        true
+    }
+
+    pub fn move_to_back(&mut self, handle: NodeHandle) -> bool {
+        let Some(node) = self.resolve(handle) else {
+            return false;
+        };
+        if self.back != Some(node) {
+            unsafe {
+                let next = (*node.as_ptr()).back;
+                let prev = (*node.as_ptr()).front;
+                (*next.unwrap().as_ptr()).front = prev;
+                match prev {
+                    Some(prev) => (*prev.as_ptr()).back = next,
+                    None => self.front = next,
                 }
+                self.len -= 1;
+                self.link_chain(self.back, None, node, node, 1);
             }
         }
-        panic!("Invalid index");
+        true
     }
 
     pub fn front(&self) -> Option<&T> {
@@ -199,7 +729,39 @@ impl<T> DoublyLinkedList<T> {
         unsafe { self.back.map(|node| &mut (*node.as_ptr()).elem) }
     }
 
-    pub fn iter(&self) -> DoublyLinkedListIter<T> {
+    pub fn cursor_front(&self) -> Cursor<'_, T> {
+        Cursor {
+            list: self,
+            current: self.front,
+            index: 0,
+        }
+    }
+
+    pub fn cursor_back(&self) -> Cursor<'_, T> {
+        Cursor {
+            list: self,
+            current: self.back,
+            index: self.len.saturating_sub(1),
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.front,
+            index: 0,
+            list: self,
+        }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.back,
+            index: self.len.saturating_sub(1),
+            list: self,
+        }
+    }
+
+    pub fn iter(&self) -> DoublyLinkedListIter<'_, T> {
         DoublyLinkedListIter {
             front: self.front,
             back: self.back,
@@ -208,7 +770,7 @@ impl<T> DoublyLinkedList<T> {
         }
     }
 
-    pub fn iter_mut(&self) -> DoublyLinkedListIterMut<T> {
+    pub fn iter_mut(&mut self) -> DoublyLinkedListIterMut<'_, T> {
         DoublyLinkedListIterMut {
             front: self.front,
             back: self.back,
@@ -222,18 +784,168 @@ impl<T> DoublyLinkedList<T> {
     }
 }
 
+#[cfg(test)]
+impl<T> DoublyLinkedList<T> {
+    fn assert_invariants(&self) {
+        unsafe {
+            assert_eq!(self.front.is_none(), self.len == 0);
+            assert_eq!(self.back.is_none(), self.len == 0);
+            if let Some(front) = self.front {
+                assert!((*front.as_ptr()).front.is_none());
+            }
+            let mut prev: Link<T> = None;
+            let mut curr = self.front;
+            let mut count = 0;
+            while let Some(node) = curr {
+                assert_eq!((*node.as_ptr()).front, prev);
+                count += 1;
+                assert!(count <= self.len, "list is longer than its length");
+                prev = curr;
+                curr = (*node.as_ptr()).back;
+            }
+            assert_eq!(prev, self.back);
+            assert_eq!(count, self.len);
+
+            let mut handled = 0;
+            let mut curr = self.front;
+            while let Some(node) = curr {
+                // Nodes that arrived from another list may still carry a stale slot.
+                let slot = (*node.as_ptr()).slot;
+                if self
+                    .slots
+                    .get(slot as usize)
+                    .is_some_and(|entry| entry.node == Some(node))
+                {
+                    handled += 1;
+                }
+                curr = (*node.as_ptr()).back;
+            }
+            assert_eq!(handled, self.slots.len() - self.free_slots.len());
+            for (slot, entry) in self.slots.iter().enumerate() {
+                assert_eq!(
+                    entry.node.is_none(),
+                    self.free_slots.contains(&(slot as u32))
+                );
+            }
+        }
+    }
+}
+
 impl<T> Default for DoublyLinkedList<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl<T: Clone> Clone for DoublyLinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DoublyLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for DoublyLinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for DoublyLinkedList<T> {}
+
+impl<T: PartialOrd> PartialOrd for DoublyLinkedList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for DoublyLinkedList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for DoublyLinkedList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        for elem in self {
+            elem.hash(state);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = DoublyLinkedList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for DoublyLinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a> Extend<&'a T> for DoublyLinkedList<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
 impl<T> Drop for DoublyLinkedList<T> {
     fn drop(&mut self) {
-        while self.pop_front().is_some() {}
+        // Keeps freeing the remaining nodes if dropping an element panics.
+        struct DropGuard<'a, T>(&'a mut DoublyLinkedList<T>);
+
+        impl<'a, T> Drop for DropGuard<'a, T> {
+            fn drop(&mut self) {
+                while self.0.pop_front().is_some() {}
+            }
+        }
+
+        let guard = DropGuard(self);
+        while guard.0.pop_front().is_some() {}
+        mem::forget(guard);
     }
 }
 
+// The raw links opt the list out of the auto traits. Every node, and the slot-table entry
+// pointing at it, is reachable only through the one list that allocated it, and the list only
+// lends out `&T` from `&self`; so moving the list moves its `T`s and sharing it shares them.
+unsafe impl<T: Send> Send for DoublyLinkedList<T> {}
+unsafe impl<T: Sync> Sync for DoublyLinkedList<T> {}
+
+// Yields nothing but `&'a T` out of a list borrowed for `'a`, so it may go wherever a
+// `&DoublyLinkedList<T>` may.
+unsafe impl<'a, T: Sync> Send for DoublyLinkedListIter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for DoublyLinkedListIter<'a, T> {}
+
+// Yields disjoint `&'a mut T`s; it cannot reach the list's structure, only the elements, so it
+// needs exactly what a `&mut T` needs.
+unsafe impl<'a, T: Send> Send for DoublyLinkedListIterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for DoublyLinkedListIterMut<'a, T> {}
+
+// Holds a `&DoublyLinkedList<T>` plus a position inside it, and reads through nothing else.
+unsafe impl<'a, T: Sync> Send for Cursor<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Cursor<'a, T> {}
+
+// Holds the list's `&mut`, and through `&self` only ever hands out `&T`.
+unsafe impl<'a, T: Send> Send for CursorMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for CursorMut<'a, T> {}
+
+// Moves elements out of a mutably borrowed list by value and runs `pred` wherever it is
+// driven from, so both must be `Send`. `&ExtractIf` exposes nothing.
+unsafe impl<'a, T: Send, F: Send> Send for ExtractIf<'a, T, F> {}
+unsafe impl<'a, T: Sync, F: Sync> Sync for ExtractIf<'a, T, F> {}
+
 impl<'a, T> IntoIterator for &'a DoublyLinkedList<T> {
     type Item = &'a T;
 
@@ -244,6 +956,18 @@ impl<'a, T> IntoIterator for &'a DoublyLinkedList<T> {
     }
 }
 
+impl<'a, T> Clone for DoublyLinkedListIter<'a, T> {
+    fn clone(&self) -> Self {
+        DoublyLinkedListIter { ..*self }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for DoublyLinkedListIter<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
 impl<'a, T> Iterator for DoublyLinkedListIter<'a, T> {
     type Item = &'a T;
 
@@ -362,35 +1086,311 @@ impl<T> ExactSizeIterator for IntoDoublyLinkedListIter<T> {
 
 impl<'a, T> ExactSizeIterator for DoublyLinkedListIter<'a, T> {}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl<'a, T> FusedIterator for DoublyLinkedListIter<'a, T> {}
 
-    #[test]
-    fn it_works() {
-        let mut list = DoublyLinkedList::new();
+impl<'a, T> FusedIterator for DoublyLinkedListIterMut<'a, T> {}
 
-        // Try to break an empty list
-        assert_eq!(list.len(), 0);
-        assert_eq!(list.pop_front(), None);
-        assert_eq!(list.len(), 0);
+impl<T> FusedIterator for IntoDoublyLinkedListIter<T> {}
 
-        // Try to break a one item list
-        list.push_front(10);
-        assert_eq!(list.len(), 1);
-        assert_eq!(list.pop_front(), Some(10));
-        assert_eq!(list.len(), 0);
-        assert_eq!(list.pop_front(), None);
-        assert_eq!(list.len(), 0);
+impl<'a, T, F> Iterator for ExtractIf<'a, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
 
-        // Mess around
-        list.push_front(10);
-        assert_eq!(list.len(), 1);
-        list.push_front(20);
-        assert_eq!(list.len(), 2);
-        list.push_front(30);
-        assert_eq!(list.len(), 3);
-        assert_eq!(list.pop_front(), Some(30));
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            while let Some(node) = self.current {
+                self.current = (*node.as_ptr()).back;
+                if (self.pred)(&mut (*node.as_ptr()).elem) {
+                    return Some(self.list.unlink_node(node).elem);
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.list.len))
+    }
+}
+
+impl<'a, T, F> FusedIterator for ExtractIf<'a, T, F> where F: FnMut(&mut T) -> bool {}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Drain<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Drain<'a, T> {}
+
+impl<'a, T> FusedIterator for Drain<'a, T> {}
+
+impl<'a, T: fmt::Debug> fmt::Debug for Drain<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Drain").field(&self.list).finish()
+    }
+}
+
+// A cursor sits either on an element or on the "ghost" position between the back and the
+// front of the list, which is where it ends up after walking off either end. While on the
+// ghost, `index` is equal to the list length.
+impl<'a, T> Clone for Cursor<'a, T> {
+    fn clone(&self) -> Self {
+        Cursor { ..*self }
+    }
+}
+
+impl<'a, T> Cursor<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn move_next(&mut self) {
+        match self.current {
+            Some(node) => unsafe {
+                self.current = (*node.as_ptr()).back;
+                self.index += 1;
+            },
+            None => {
+                self.current = self.list.front;
+                self.index = 0;
+            }
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        match self.current {
+            Some(node) => unsafe {
+                self.current = (*node.as_ptr()).front;
+                self.index = self.index.checked_sub(1).unwrap_or(self.list.len);
+            },
+            None => {
+                self.current = self.list.back;
+                self.index = self.list.len.saturating_sub(1);
+            }
+        }
+    }
+
+    pub fn current(&self) -> Option<&'a T> {
+        unsafe { self.current.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_next(&self) -> Option<&'a T> {
+        unsafe {
+            let next = match self.current {
+                Some(node) => (*node.as_ptr()).back,
+                None => self.list.front,
+            };
+            next.map(|node| &(*node.as_ptr()).elem)
+        }
+    }
+
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        unsafe {
+            let prev = match self.current {
+                Some(node) => (*node.as_ptr()).front,
+                None => self.list.back,
+            };
+            prev.map(|node| &(*node.as_ptr()).elem)
+        }
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn move_next(&mut self) {
+        match self.current {
+            Some(node) => unsafe {
+                self.current = (*node.as_ptr()).back;
+                self.index += 1;
+            },
+            None => {
+                self.current = self.list.front;
+                self.index = 0;
+            }
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        match self.current {
+            Some(node) => unsafe {
+                self.current = (*node.as_ptr()).front;
+                self.index = self.index.checked_sub(1).unwrap_or(self.list.len);
+            },
+            None => {
+                self.current = self.list.back;
+                self.index = self.list.len.saturating_sub(1);
+            }
+        }
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.current.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        unsafe {
+            let next = match self.current {
+                Some(node) => (*node.as_ptr()).back,
+                None => self.list.front,
+            };
+            next.map(|node| &mut (*node.as_ptr()).elem)
+        }
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        unsafe {
+            let prev = match self.current {
+                Some(node) => (*node.as_ptr()).front,
+                None => self.list.back,
+            };
+            prev.map(|node| &mut (*node.as_ptr()).elem)
+        }
+    }
+
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            list: self.list,
+            current: self.current,
+            index: self.index,
+        }
+    }
+
+    pub fn insert_after(&mut self, elem: T) {
+        let mut single = DoublyLinkedList::new();
+        single.push_back(elem);
+        self.splice_after(single);
+    }
+
+    pub fn insert_before(&mut self, elem: T) {
+        let mut single = DoublyLinkedList::new();
+        single.push_back(elem);
+        self.splice_before(single);
+    }
+
+    pub fn insert_after_handle(&mut self, elem: T) -> NodeHandle {
+        self.insert_after(elem);
+        let node = match self.current {
+            Some(node) => unsafe { (*node.as_ptr()).back },
+            None => self.list.front,
+        };
+        self.list.attach_handle(node.unwrap())
+    }
+
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        unsafe {
+            self.current = (*node.as_ptr()).back;
+            Some(self.list.unlink_node(node).elem)
+        }
+    }
+
+    pub fn split_after(&mut self) -> DoublyLinkedList<T> {
+        let count = match self.current {
+            Some(_) => self.list.len - self.index - 1,
+            None => self.list.len,
+        };
+        let split = unsafe { self.list.split_after_node(self.current, count) };
+        if self.current.is_none() {
+            self.index = 0;
+        }
+        split
+    }
+
+    pub fn split_before(&mut self) -> DoublyLinkedList<T> {
+        let count = match self.current {
+            Some(_) => self.index,
+            None => self.list.len,
+        };
+        self.index = 0;
+        unsafe { self.list.split_before_node(self.current, count) }
+    }
+
+    pub fn splice_after(&mut self, other: DoublyLinkedList<T>) {
+        unsafe {
+            let next = match self.current {
+                Some(node) => (*node.as_ptr()).back,
+                None => {
+                    self.index += other.len;
+                    self.list.front
+                }
+            };
+            self.list.splice_list(self.current, next, other);
+        }
+    }
+
+    pub fn splice_before(&mut self, other: DoublyLinkedList<T>) {
+        unsafe {
+            let prev = match self.current {
+                Some(node) => (*node.as_ptr()).front,
+                None => self.list.back,
+            };
+            self.index += other.len;
+            self.list.splice_list(prev, self.current, other);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{DropCounter, PanicOnDrop};
+    use rand::Rng;
+    use std::{cell::Cell, collections::VecDeque};
+
+    thread_local! {
+        // Nodes visited while retiring the handles of a moved chain.
+        pub(super) static HANDLE_WALK_STEPS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    fn handle_walk_steps<R>(f: impl FnOnce() -> R) -> (R, usize) {
+        let before = HANDLE_WALK_STEPS.with(Cell::get);
+        let result = f();
+        (result, HANDLE_WALK_STEPS.with(Cell::get) - before)
+    }
+
+    #[test]
+    fn it_works() {
+        let mut list = DoublyLinkedList::new();
+
+        // Try to break an empty list
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.len(), 0);
+
+        // Try to break a one item list
+        list.push_front(10);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.len(), 0);
+
+        // Mess around
+        list.push_front(10);
+        assert_eq!(list.len(), 1);
+        list.push_front(20);
+        assert_eq!(list.len(), 2);
+        list.push_front(30);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_front(), Some(30));
         assert_eq!(list.len(), 2);
         list.push_front(40);
         assert_eq!(list.len(), 3);
@@ -416,7 +1416,7 @@ mod tests {
         list.push_front(5);
 
         assert_eq!(list.len(), 5);
-        list.remove_at(2);
+        assert_eq!(list.remove_at(2), Some(3));
         assert_eq!(list.len(), 4);
         assert_eq!(list.pop_back(), Some(1));
         assert_eq!(list.len(), 3);
@@ -428,6 +1428,96 @@ mod tests {
         assert_eq!(list.len(), 0);
     }
 
+    #[test]
+    fn remove_at_ends() {
+        let mut list = from_slice(&[1, 2, 3]);
+        assert_eq!(list.remove_at(3), None);
+        assert_eq!(list.remove_at(0), Some(1));
+        assert_eq!(list.front(), Some(&2));
+        assert_eq!(list.remove_at(1), Some(3));
+        assert_eq!(list.back(), Some(&2));
+        assert_eq!(list.remove_at(0), Some(2));
+        assert_eq!(list.front(), None);
+        assert_eq!(list.back(), None);
+        assert_eq!(list.remove_at(0), None);
+        assert_eq!(contents(&list), vec![]);
+    }
+
+    #[test]
+    fn insert_at_ends() {
+        let mut list = DoublyLinkedList::new();
+        assert_eq!(list.insert_at(2, 1), Err(2));
+        assert_eq!(list.insert_at(2, 0), Ok(()));
+        assert_eq!(list.insert_at(1, 0), Ok(()));
+        assert_eq!(list.insert_at(4, 2), Ok(()));
+        assert_eq!(list.insert_at(3, 2), Ok(()));
+        assert_eq!(list.insert_at(9, 5), Err(9));
+        assert_eq!(contents(&list), vec![1, 2, 3, 4]);
+        assert_eq!(list.front(), Some(&1));
+        assert_eq!(list.back(), Some(&4));
+    }
+
+    #[test]
+    fn index_ops_match_vecdeque_exhaustively() {
+        for len in 0..7 {
+            let items: Vec<i32> = (0..len).collect();
+            for index in 0..=len as usize + 1 {
+                let mut list = from_slice(&items);
+                let mut model: VecDeque<i32> = items.iter().copied().collect();
+                assert_eq!(list.remove_at(index), model.remove(index));
+                assert_eq!(contents(&list), Vec::from(model));
+
+                let mut list = from_slice(&items);
+                let mut model: VecDeque<i32> = items.iter().copied().collect();
+                let expected = if index <= model.len() {
+                    model.insert(index, -1);
+                    Ok(())
+                } else {
+                    Err(-1)
+                };
+                assert_eq!(list.insert_at(-1, index), expected);
+                assert_eq!(contents(&list), Vec::from(model));
+            }
+        }
+    }
+
+    #[test]
+    fn index_ops_match_vecdeque_randomly() {
+        let mut rng = rand::thread_rng();
+        let mut list = DoublyLinkedList::new();
+        let mut model = VecDeque::new();
+        for step in 0..5000 {
+            let index = rng.gen_range(0..=model.len() + 1);
+            match rng.gen_range(0..6) {
+                0 => {
+                    list.push_front(step);
+                    model.push_front(step);
+                }
+                1 => {
+                    list.push_back(step);
+                    model.push_back(step);
+                }
+                2 => assert_eq!(list.pop_front(), model.pop_front()),
+                3 => assert_eq!(list.pop_back(), model.pop_back()),
+                4 => assert_eq!(list.remove_at(index), model.remove(index)),
+                _ => {
+                    let expected = if index <= model.len() {
+                        model.insert(index, step);
+                        Ok(())
+                    } else {
+                        Err(step)
+                    };
+                    assert_eq!(list.insert_at(step, index), expected);
+                }
+            }
+            assert_eq!(list.front(), model.front());
+            assert_eq!(list.back(), model.back());
+            assert_eq!(list.len(), model.len());
+            list.assert_invariants();
+        }
+        assert_eq!(contents(&list), Vec::from(model));
+    }
+
     #[test]
     fn insert_at_works() {
         let mut list = DoublyLinkedList::new();
@@ -438,19 +1528,783 @@ mod tests {
         list.push_front(5);
 
         assert_eq!(list.len(), 5);
-        list.insert_at(6, 2);
+        assert_eq!(list.insert_at(6, 2), Ok(()));
         assert_eq!(list.len(), 6);
         assert_eq!(list.pop_back(), Some(1));
         assert_eq!(list.len(), 5);
         assert_eq!(list.pop_back(), Some(2));
         assert_eq!(list.len(), 4);
-        assert_eq!(list.pop_back(), Some(6));
-        assert_eq!(list.len(), 3);
         assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_back(), Some(6));
         assert_eq!(list.len(), 2);
         assert_eq!(list.pop_back(), Some(4));
         assert_eq!(list.len(), 1);
         assert_eq!(list.pop_back(), Some(5));
         assert_eq!(list.len(), 0);
     }
+
+    fn contents(list: &DoublyLinkedList<i32>) -> Vec<i32> {
+        list.assert_invariants();
+        let forward: Vec<i32> = list.iter().copied().collect();
+        let mut backward: Vec<i32> = list.iter().rev().copied().collect();
+        backward.reverse();
+        assert_eq!(forward, backward);
+        assert_eq!(forward.len(), list.len());
+        forward
+    }
+
+    fn from_slice(items: &[i32]) -> DoublyLinkedList<i32> {
+        let mut list = DoublyLinkedList::new();
+        for &item in items {
+            list.push_back(item);
+        }
+        list
+    }
+
+    #[test]
+    fn cursor_moves() {
+        let list = from_slice(&[1, 2, 3]);
+        let mut cursor = list.cursor_front();
+        assert_eq!(cursor.current(), Some(&1));
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.peek_prev(), None);
+        assert_eq!(cursor.peek_next(), Some(&2));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&3));
+        assert_eq!(cursor.index(), Some(2));
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.peek_next(), Some(&1));
+        assert_eq!(cursor.peek_prev(), Some(&3));
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&1));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&3));
+        assert_eq!(cursor.index(), Some(2));
+
+        let mut cursor = list.cursor_back();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&2));
+        assert_eq!(cursor.index(), Some(1));
+
+        let empty: DoublyLinkedList<i32> = DoublyLinkedList::new();
+        let mut cursor = empty.cursor_back();
+        assert_eq!(cursor.current(), None);
+        cursor.move_next();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), None);
+    }
+
+    #[test]
+    fn cursor_mut_edits() {
+        let mut list = from_slice(&[1, 2, 3, 4, 5, 6]);
+        let mut cursor = list.cursor_front_mut();
+        while let Some(value) = cursor.current() {
+            if *value % 2 == 0 {
+                let value = cursor.remove_current().unwrap();
+                cursor.insert_before(value * 10);
+            } else {
+                *value += 100;
+                cursor.insert_after(0);
+                cursor.move_next();
+                cursor.move_next();
+            }
+        }
+        assert_eq!(cursor.index(), None);
+        cursor.insert_after(-1);
+        cursor.insert_before(-2);
+        assert_eq!(cursor.peek_next(), Some(&mut -1));
+        assert_eq!(cursor.peek_prev(), Some(&mut -2));
+        assert_eq!(
+            contents(&list),
+            vec![-1, 101, 0, 20, 103, 0, 40, 105, 0, 60, -2]
+        );
+
+        let mut list = from_slice(&[7]);
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!(cursor.remove_current(), Some(7));
+        assert_eq!(cursor.remove_current(), None);
+        assert_eq!(cursor.current(), None);
+        cursor.insert_before(8);
+        assert_eq!(cursor.as_cursor().peek_prev(), Some(&8));
+        assert_eq!(contents(&list), vec![8]);
+    }
+
+    #[test]
+    fn cursor_split_and_splice() {
+        let mut list = from_slice(&[1, 2, 3, 4, 5]);
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let after = cursor.split_after();
+        assert_eq!(cursor.index(), Some(2));
+        let before = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        assert_eq!(contents(&before), vec![1, 2]);
+        assert_eq!(contents(&after), vec![4, 5]);
+        assert_eq!(contents(&list), vec![3]);
+
+        let mut cursor = list.cursor_front_mut();
+        cursor.splice_after(after);
+        cursor.splice_before(before);
+        assert_eq!(cursor.index(), Some(2));
+        cursor.splice_before(DoublyLinkedList::new());
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        cursor.splice_after(from_slice(&[-2, -1]));
+        cursor.splice_before(from_slice(&[6, 7]));
+        assert_eq!(contents(&list), vec![-2, -1, 1, 2, 3, 4, 5, 6, 7]);
+
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_prev();
+        let whole = cursor.split_after();
+        assert_eq!(contents(&whole), vec![-2, -1, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(contents(&list), vec![]);
+        let mut list = whole;
+        let mut cursor = list.cursor_back_mut();
+        cursor.move_next();
+        let whole = cursor.split_before();
+        assert_eq!(contents(&whole), vec![-2, -1, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(contents(&list), vec![]);
+
+        let mut list = from_slice(&[1, 2]);
+        let mut cursor = list.cursor_back_mut();
+        assert!(cursor.split_after().iter().next().is_none());
+        cursor.move_prev();
+        assert!(cursor.split_before().iter().next().is_none());
+        assert_eq!(contents(&list), vec![1, 2]);
+    }
+
+    #[test]
+    fn iterators() {
+        let mut list = from_slice(&[1, 2, 3, 4, 5]);
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+
+        let mut iter = list.iter_mut();
+        *iter.next().unwrap() *= 10;
+        *iter.next_back().unwrap() *= 10;
+        for item in iter {
+            *item += 1;
+        }
+        assert_eq!(contents(&list), vec![10, 3, 4, 5, 50]);
+        for item in &mut list {
+            *item -= 1;
+        }
+        assert_eq!((&list).into_iter().sum::<i32>(), 67);
+
+        let mut into_iter = list.into_iter();
+        assert_eq!(into_iter.len(), 5);
+        assert_eq!(into_iter.next_back(), Some(49));
+        assert_eq!(into_iter.next(), Some(9));
+        assert_eq!(into_iter.collect::<Vec<_>>(), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn drops_every_element_once() {
+        let drops = std::cell::Cell::new(0);
+        let mut list = DoublyLinkedList::new();
+        for _ in 0..10 {
+            list.push_back(DropCounter(&drops));
+        }
+        drop(list.pop_front());
+        drop(list.pop_back());
+        drop(list.remove_at(3));
+        assert_eq!(drops.get(), 3);
+
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        drop(cursor.remove_current());
+        let split = cursor.split_after();
+        assert_eq!(drops.get(), 4);
+        drop(split);
+        assert_eq!(drops.get(), 4 + 4);
+        list.assert_invariants();
+
+        let mut into_iter = list.into_iter();
+        drop(into_iter.next());
+        assert_eq!(drops.get(), 9);
+        drop(into_iter);
+        assert_eq!(drops.get(), 10);
+    }
+
+    #[test]
+    fn drop_continues_after_panic() {
+        let drops = std::cell::Cell::new(0);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut list = DoublyLinkedList::new();
+            list.push_back(PanicOnDrop(&drops, false));
+            list.push_back(PanicOnDrop(&drops, true));
+            list.push_back(PanicOnDrop(&drops, false));
+            list.push_back(PanicOnDrop(&drops, false));
+        }));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 4);
+    }
+
+    #[test]
+    fn send_and_sync() {
+        fn assert_send<T: Send>() {}
+        fn assert_sync<T: Sync>() {}
+        assert_send::<DoublyLinkedList<i32>>();
+        assert_sync::<DoublyLinkedList<i32>>();
+        assert_send::<DoublyLinkedListIter<'_, i32>>();
+        assert_sync::<DoublyLinkedListIter<'_, i32>>();
+        assert_send::<DoublyLinkedListIterMut<'_, i32>>();
+        assert_sync::<DoublyLinkedListIterMut<'_, i32>>();
+        assert_send::<IntoDoublyLinkedListIter<i32>>();
+        assert_send::<Cursor<'_, i32>>();
+        assert_send::<CursorMut<'_, i32>>();
+
+        let mut list = from_slice(&[1, 2, 3, 4]);
+        std::thread::scope(|scope| {
+            let shared = &list;
+            let a = scope.spawn(move || shared.iter().sum::<i32>());
+            let b = scope.spawn(move || shared.iter().rev().max().copied());
+            assert_eq!(a.join().unwrap(), 10);
+            assert_eq!(b.join().unwrap(), Some(4));
+        });
+        std::thread::scope(|scope| {
+            let iter = list.iter_mut();
+            scope.spawn(move || iter.for_each(|item| *item *= 2));
+        });
+        let list = std::thread::spawn(move || {
+            list.push_back(10);
+            list
+        })
+        .join()
+        .unwrap();
+        assert_eq!(contents(&list), vec![2, 4, 6, 8, 10]);
+    }
+
+    #[test]
+    fn variance() {
+        fn list<'a>(list: DoublyLinkedList<&'static str>) -> DoublyLinkedList<&'a str> {
+            list
+        }
+        fn iter<'i, 'a>(
+            iter: DoublyLinkedListIter<'i, &'static str>,
+        ) -> DoublyLinkedListIter<'i, &'a str> {
+            iter
+        }
+        fn into_iter<'a>(
+            iter: IntoDoublyLinkedListIter<&'static str>,
+        ) -> IntoDoublyLinkedListIter<&'a str> {
+            iter
+        }
+        fn cursor<'c, 'a>(cursor: Cursor<'c, &'static str>) -> Cursor<'c, &'a str> {
+            cursor
+        }
+
+        fn from_strs(items: &[&'static str]) -> DoublyLinkedList<&'static str> {
+            let mut list = DoublyLinkedList::new();
+            for &item in items {
+                list.push_back(item);
+            }
+            list
+        }
+
+        let static_list = from_strs(&["static"]);
+        let local = String::from("local");
+        let mut items = iter(static_list.iter());
+        assert!(items.any(|item| *item != local.as_str()));
+        assert_eq!(
+            cursor(static_list.cursor_front()).current(),
+            Some(&"static")
+        );
+        let mut into = into_iter(from_strs(&["static"]).into_iter());
+        assert_ne!(into.next(), Some(local.as_str()));
+        let mut shorter = list(static_list);
+        shorter.push_back(&local);
+        assert_eq!(shorter.back(), Some(&"local"));
+    }
+
+    #[test]
+    fn append_and_prepend() {
+        let mut list = from_slice(&[3, 4]);
+        let mut other = from_slice(&[5, 6]);
+        list.append(&mut other);
+        assert_eq!(contents(&other), vec![]);
+        let mut other = from_slice(&[1, 2]);
+        list.prepend(&mut other);
+        assert_eq!(contents(&other), vec![]);
+        list.append(&mut other);
+        list.prepend(&mut other);
+        assert_eq!(contents(&list), vec![1, 2, 3, 4, 5, 6]);
+
+        let mut empty = DoublyLinkedList::new();
+        empty.append(&mut list);
+        assert_eq!(contents(&empty), vec![1, 2, 3, 4, 5, 6]);
+        list.prepend(&mut empty);
+        assert_eq!(contents(&list), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(contents(&empty), vec![]);
+    }
+
+    #[test]
+    fn split_off_and_splice() {
+        for at in 0..=6 {
+            let mut list = from_slice(&[0, 1, 2, 3, 4, 5]);
+            let tail = list.split_off(at);
+            assert_eq!(contents(&list), (0..at as i32).collect::<Vec<_>>());
+            assert_eq!(contents(&tail), (at as i32..6).collect::<Vec<_>>());
+
+            list.append(&mut from_slice(&[10, 11]));
+            list.splice(at, tail);
+            assert_eq!(contents(&list), vec![0, 1, 2, 3, 4, 5, 10, 11]);
+        }
+
+        let mut list = from_slice(&[1, 4]);
+        list.splice(1, from_slice(&[2, 3]));
+        list.splice(0, from_slice(&[0]));
+        list.splice(5, from_slice(&[5]));
+        list.splice(2, DoublyLinkedList::new());
+        assert_eq!(contents(&list), vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_off_out_of_bounds() {
+        from_slice(&[1, 2]).split_off(3);
+    }
+
+    #[test]
+    fn standard_traits() {
+        use std::collections::{hash_map::DefaultHasher, HashMap, LinkedList};
+
+        fn hash_of<H: Hash>(value: &H) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let list: DoublyLinkedList<i32> = (1..=3).collect();
+        let cloned = list.clone();
+        assert_eq!(contents(&cloned), vec![1, 2, 3]);
+        assert_eq!(list, cloned);
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+        let mut iter = list.iter();
+        iter.next();
+        assert_eq!(format!("{:?}", iter), "[2, 3]");
+        assert_eq!(
+            format!("{:?}", DoublyLinkedList::<i32>::new()),
+            format!("{:?}", LinkedList::<i32>::new())
+        );
+
+        let shorter: DoublyLinkedList<i32> = [1, 2].into_iter().collect();
+        let bigger: DoublyLinkedList<i32> = [1, 3].into_iter().collect();
+        assert_ne!(list, shorter);
+        assert!(shorter < list);
+        assert!(list < bigger);
+        assert_eq!(list.cmp(&cloned), Ordering::Equal);
+        let nan: DoublyLinkedList<f64> = [f64::NAN].into_iter().collect();
+        assert_eq!(nan.partial_cmp(&nan), None);
+        assert_ne!(nan, nan);
+
+        assert_eq!(hash_of(&list), hash_of(&cloned));
+        assert_eq!(
+            hash_of(&list),
+            hash_of(&(1..=3).collect::<LinkedList<i32>>())
+        );
+        let empty_then_one: (DoublyLinkedList<i32>, DoublyLinkedList<i32>) =
+            (DoublyLinkedList::new(), [1].into_iter().collect());
+        let one_then_empty = (empty_then_one.1.clone(), empty_then_one.0.clone());
+        assert_ne!(hash_of(&empty_then_one), hash_of(&one_then_empty));
+
+        let mut counts = HashMap::new();
+        *counts.entry(list.clone()).or_insert(0) += 1;
+        *counts.entry(cloned).or_insert(0) += 1;
+        assert_eq!(counts[&list], 2);
+
+        let mut extended = list.clone();
+        extended.extend(vec![4, 5]);
+        extended.extend(&[6]);
+        assert_eq!(contents(&extended), vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn sort() {
+        let mut rng = rand::thread_rng();
+        for len in [0, 1, 2, 3, 7, 64, 65, 1000] {
+            let items: Vec<i32> = (0..len).map(|_| rng.gen_range(-50..50)).collect();
+            let mut list = from_slice(&items);
+            list.sort();
+            let mut expected = items.clone();
+            expected.sort();
+            assert_eq!(contents(&list), expected);
+
+            let mut list = from_slice(&items);
+            list.sort_by(|a, b| b.cmp(a));
+            expected.reverse();
+            assert_eq!(contents(&list), expected);
+        }
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        let mut rng = rand::thread_rng();
+        let items: Vec<(i32, usize)> = (0..500).map(|i| (rng.gen_range(0..10), i)).collect();
+        let mut list: DoublyLinkedList<(i32, usize)> = items.iter().copied().collect();
+        list.sort_by_key(|&(key, _)| key);
+        let mut expected = items.clone();
+        expected.sort_by_key(|&(key, _)| key);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+        list.assert_invariants();
+    }
+
+    #[test]
+    fn sort_keeps_elements_when_compare_panics() {
+        let mut list = from_slice(&[5, 3, 8, 1, 9, 2, 7]);
+        let mut calls = 0;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            list.sort_by(|a, b| {
+                calls += 1;
+                if calls == 6 {
+                    panic!("bad comparator");
+                }
+                a.cmp(b)
+            })
+        }));
+        assert!(result.is_err());
+        let mut items = contents(&list);
+        items.sort();
+        assert_eq!(items, vec![1, 2, 3, 5, 7, 8, 9]);
+    }
+
+    #[test]
+    fn merge() {
+        let mut list = from_slice(&[1, 3, 5, 7]);
+        let mut other = from_slice(&[0, 2, 3, 8, 9]);
+        list.merge(&mut other);
+        assert_eq!(contents(&list), vec![0, 1, 2, 3, 3, 5, 7, 8, 9]);
+        assert_eq!(contents(&other), vec![]);
+
+        let mut list: DoublyLinkedList<(i32, char)> = [(1, 'a'), (2, 'a')].into_iter().collect();
+        let mut other: DoublyLinkedList<(i32, char)> = [(1, 'b'), (2, 'b')].into_iter().collect();
+        list.merge_by(&mut other, |a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            list.into_iter().collect::<Vec<_>>(),
+            vec![(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]
+        );
+
+        let mut empty = DoublyLinkedList::new();
+        let mut other = from_slice(&[1, 2]);
+        empty.merge(&mut other);
+        assert_eq!(contents(&empty), vec![1, 2]);
+    }
+
+    #[test]
+    fn dedup() {
+        let mut list = from_slice(&[1, 1, 2, 3, 3, 3, 1, 4, 4]);
+        list.dedup();
+        assert_eq!(contents(&list), vec![1, 2, 3, 1, 4]);
+
+        let mut list = from_slice(&[10, 11, 20, 25, 31, 12]);
+        list.dedup_by_key(|x| *x / 10);
+        assert_eq!(contents(&list), vec![10, 20, 31, 12]);
+
+        let mut list = from_slice(&[1, 2, 4, 5, 7]);
+        list.dedup_by(|a, b| *a - *b == 1);
+        assert_eq!(contents(&list), vec![1, 4, 7]);
+
+        let mut list = from_slice(&[]);
+        list.dedup();
+        assert_eq!(contents(&list), vec![]);
+    }
+
+    #[test]
+    fn node_handles() {
+        let mut list = DoublyLinkedList::new();
+        let one = list.push_back_handle(1);
+        let two = list.push_back_handle(2);
+        list.push_back(3);
+        let zero = list.push_front_handle(0);
+        assert_eq!(list.get(one), Some(&1));
+        *list.get_mut(two).unwrap() = 20;
+        assert!(list.move_to_front(two));
+        assert!(list.move_to_back(zero));
+        assert!(list.move_to_front(two));
+        assert_eq!(contents(&list), vec![20, 1, 3, 0]);
+
+        assert_eq!(list.remove(one), Some(1));
+        assert_eq!(list.remove(one), None);
+        assert_eq!(list.get(one), None);
+        assert!(!list.move_to_front(one));
+        assert!(!list.move_to_back(one));
+
+        // The freed slot gets reused, but the old handle stays dead.
+        let four = list.push_back_handle(4);
+        assert_eq!(list.get(one), None);
+        assert_eq!(list.get(four), Some(&4));
+
+        assert_eq!(list.pop_front(), Some(20));
+        assert_eq!(list.get(two), None);
+        assert_eq!(list.pop_back(), Some(4));
+        assert_eq!(list.get(four), None);
+        assert_eq!(list.remove_at(1), Some(0));
+        assert_eq!(list.get(zero), None);
+        assert_eq!(contents(&list), vec![3]);
+
+        let mut cursor = list.cursor_front_mut();
+        let after = cursor.insert_after_handle(5);
+        cursor.move_prev();
+        let first = cursor.insert_after_handle(1);
+        assert_eq!(list.get(after), Some(&5));
+        assert_eq!(list.remove(first), Some(1));
+        assert_eq!(contents(&list), vec![3, 5]);
+        list.assert_invariants();
+    }
+
+    #[test]
+    fn node_handles_are_bound_to_their_list() {
+        let mut a = DoublyLinkedList::new();
+        let mut b = DoublyLinkedList::new();
+        let in_a = a.push_back_handle(1);
+        let in_b = b.push_back_handle(2);
+        assert_eq!(b.get(in_a), None);
+        assert_eq!(a.remove(in_b), None);
+        assert_eq!(DoublyLinkedList::<i32>::new().get(in_a), None);
+        assert_eq!(a.clone().get(in_a), None);
+
+        a.append(&mut b);
+        assert_eq!(a.get(in_a), Some(&1));
+        assert_eq!(a.get(in_b), None);
+        assert_eq!(b.get(in_b), None);
+
+        let moved = a.push_back_handle(3);
+        let tail = a.split_off(1);
+        assert_eq!(a.get(in_a), Some(&1));
+        assert_eq!(a.get(moved), None);
+        assert_eq!(tail.get(moved), None);
+        assert_eq!(contents(&a), vec![1]);
+        assert_eq!(contents(&tail), vec![2, 3]);
+
+        let mut other = DoublyLinkedList::new();
+        let merged = other.push_back_handle(0);
+        a.merge(&mut other);
+        assert_eq!(a.get(merged), None);
+        assert_eq!(contents(&a), vec![0, 1]);
+
+        let mut cursor = a.cursor_front_mut();
+        cursor.move_next();
+        let front = cursor.split_before();
+        assert_eq!(a.get(in_a), Some(&1));
+        assert_eq!(contents(&front), vec![0]);
+    }
+
+    #[test]
+    fn moving_whole_lists_does_not_walk_handles() {
+        let mut a = DoublyLinkedList::new();
+        let mut b = DoublyLinkedList::new();
+        let in_a: Vec<NodeHandle> = (0..1000).map(|i| a.push_back_handle(i)).collect();
+        let in_b: Vec<NodeHandle> = (0..1000).map(|i| b.push_back_handle(-i)).collect();
+
+        let ((), steps) = handle_walk_steps(|| a.append(&mut b));
+        assert_eq!(steps, 0);
+        assert!(in_b
+            .iter()
+            .all(|&handle| b.get(handle).is_none() && a.get(handle).is_none()));
+        // The arrivals carry slot numbers from `b`'s table that `a` also uses for its own nodes.
+        for i in 0..1000 {
+            assert_eq!(a.pop_back(), Some(-999 + i));
+        }
+        assert!(in_a
+            .iter()
+            .zip(0..)
+            .all(|(&handle, i)| a.get(handle) == Some(&i)));
+
+        let mut c = DoublyLinkedList::new();
+        let in_c = c.push_back_handle(5000);
+        let ((), steps) = handle_walk_steps(|| {
+            a.prepend(&mut c);
+            a.splice(1, from_slice(&[1, 2, 3]));
+            a.merge(&mut from_slice(&[10, 20]));
+        });
+        assert_eq!(steps, 0);
+        assert_eq!(a.get(in_c), None);
+        assert_eq!(a.get(in_a[999]), Some(&999));
+        a.assert_invariants();
+
+        let (all, steps) = handle_walk_steps(|| a.split_off(0));
+        assert_eq!(steps, 0);
+        assert!(a.is_empty() && all.len() == 1006);
+        assert!(in_a
+            .iter()
+            .all(|&handle| a.get(handle).is_none() && all.get(handle).is_none()));
+        let fresh = a.push_back_handle(7);
+        assert_eq!(a.get(fresh), Some(&7));
+        assert_eq!(a.get(in_a[0]), None);
+
+        // Splitting off part of a list still has to retire the handles that move.
+        let mut d = DoublyLinkedList::new();
+        let in_d: Vec<NodeHandle> = (0..100).map(|i| d.push_back_handle(i)).collect();
+        let (tail, steps) = handle_walk_steps(|| d.split_off(60));
+        assert_eq!(steps, 40);
+        assert_eq!(d.get(in_d[59]), Some(&59));
+        assert_eq!(d.get(in_d[60]), None);
+        assert_eq!(tail.get(in_d[60]), None);
+    }
+
+    #[test]
+    fn node_handles_survive_in_place_operations() {
+        let mut list = DoublyLinkedList::new();
+        let handles: Vec<NodeHandle> = [5, 3, 9, 1, 7]
+            .iter()
+            .map(|&x| list.push_back_handle(x))
+            .collect();
+        list.sort();
+        assert_eq!(contents(&list), vec![1, 3, 5, 7, 9]);
+        for (handle, value) in handles.iter().zip([5, 3, 9, 1, 7]) {
+            assert_eq!(list.get(*handle), Some(&value));
+        }
+
+        list.insert_at(4, 2).unwrap();
+        list.splice(0, from_slice(&[0]));
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(list.get(handles[3]), None);
+        assert_eq!(list.get(handles[4]), Some(&7));
+        assert_eq!(contents(&list), vec![0, 3, 4, 5, 7, 9]);
+    }
+
+    #[test]
+    fn node_handles_match_model() {
+        let mut rng = rand::thread_rng();
+        let mut list = DoublyLinkedList::new();
+        let mut live: Vec<(NodeHandle, i32)> = Vec::new();
+        let mut dead: Vec<NodeHandle> = Vec::new();
+        for step in 0..3000 {
+            match rng.gen_range(0..5) {
+                0 => live.push((list.push_back_handle(step), step)),
+                1 => live.push((list.push_front_handle(step), step)),
+                2 if !live.is_empty() => {
+                    let (handle, value) = live.swap_remove(rng.gen_range(0..live.len()));
+                    assert_eq!(list.remove(handle), Some(value));
+                    dead.push(handle);
+                }
+                3 if !live.is_empty() => {
+                    let (handle, _) = live[rng.gen_range(0..live.len())];
+                    assert!(list.move_to_front(handle));
+                    assert_eq!(list.get(handle), list.front());
+                }
+                _ if !live.is_empty() => {
+                    let (handle, _) = live[rng.gen_range(0..live.len())];
+                    assert!(list.move_to_back(handle));
+                    assert_eq!(list.get(handle), list.back());
+                }
+                _ => {}
+            }
+            for &handle in dead.iter().rev().take(5) {
+                assert_eq!(list.get(handle), None);
+            }
+            if step % 100 == 0 {
+                list.assert_invariants();
+            }
+        }
+        for (handle, value) in live {
+            assert_eq!(list.get(handle), Some(&value));
+        }
+        list.assert_invariants();
+    }
+
+    #[test]
+    fn retain() {
+        let mut list = from_slice(&[1, 2, 3, 4, 5, 6]);
+        let odd = list.push_back_handle(7);
+        let even = list.push_front_handle(0);
+        list.retain(|x| x % 2 == 1);
+        assert_eq!(contents(&list), vec![1, 3, 5, 7]);
+        assert_eq!(list.get(odd), Some(&7));
+        assert_eq!(list.get(even), None);
+
+        list.retain_mut(|x| {
+            *x *= 10;
+            *x != 30
+        });
+        assert_eq!(contents(&list), vec![10, 50, 70]);
+        list.retain(|_| false);
+        assert_eq!(contents(&list), vec![]);
+    }
+
+    #[test]
+    fn extract_if_is_lazy() {
+        let mut list = from_slice(&[1, 2, 3, 4, 5, 6]);
+        {
+            let mut extracted = list.extract_if(|x| *x % 2 == 0);
+            assert_eq!(extracted.next(), Some(2));
+            assert_eq!(extracted.next(), Some(4));
+        }
+        assert_eq!(contents(&list), vec![1, 3, 5, 6]);
+
+        let evens: Vec<i32> = list
+            .extract_if(|x| {
+                *x += 1;
+                *x % 2 == 0
+            })
+            .collect();
+        assert_eq!(evens, vec![2, 4, 6]);
+        assert_eq!(contents(&list), vec![7]);
+    }
+
+    #[test]
+    fn drain_matches_vec() {
+        for len in 0..6 {
+            let items: Vec<i32> = (0..len).collect();
+            for start in 0..=len as usize {
+                for end in start..=len as usize {
+                    let mut list = from_slice(&items);
+                    let mut model = items.clone();
+                    let drained: Vec<i32> = list.drain(start..end).collect();
+                    assert_eq!(drained, model.drain(start..end).collect::<Vec<_>>());
+                    assert_eq!(contents(&list), model);
+                }
+            }
+        }
+
+        let mut list = from_slice(&[1, 2, 3, 4, 5]);
+        let kept = list.push_back_handle(6);
+        let gone = list.push_front_handle(0);
+        let mut drain = list.drain(..=2);
+        assert_eq!(drain.len(), 3);
+        assert_eq!(drain.next_back(), Some(2));
+        drop(drain);
+        assert_eq!(contents(&list), vec![3, 4, 5, 6]);
+        assert_eq!(list.get(kept), Some(&6));
+        assert_eq!(list.get(gone), None);
+        assert_eq!(list.drain(..).collect::<Vec<_>>(), vec![3, 4, 5, 6]);
+        assert!(list.is_empty());
+
+        let drops = std::cell::Cell::new(0);
+        let mut list = DoublyLinkedList::new();
+        for _ in 0..5 {
+            list.push_back(DropCounter(&drops));
+        }
+        drop(list.drain(1..4));
+        assert_eq!(drops.get(), 3);
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot drain a range outside the list")]
+    fn drain_out_of_bounds() {
+        let mut list = from_slice(&[1, 2, 3]);
+        list.drain(2..4);
+    }
 }
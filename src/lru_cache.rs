@@ -0,0 +1,328 @@
+use std::{borrow::Borrow, collections::HashMap, fmt, hash::Hash};
+
+use crate::linked_list::{DoublyLinkedList, NodeHandle};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+// Entries are kept in `order` from most to least recently used, and `map` holds a handle to
+// every key's node so lookups never walk the list. A capacity of zero keeps nothing: every
+// `put` evicts the new entry straight away, calling the eviction callback for it.
+//
+// The callback's type is a parameter rather than a boxed trait object, so the cache is `Send`
+// exactly when the callback is.
+pub struct LruCache<K, V, E = fn(K, V)> {
+    map: HashMap<K, NodeHandle>,
+    order: DoublyLinkedList<(K, V)>,
+    capacity: usize,
+    on_evict: Option<E>,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> LruCache<K, V> {
+        LruCache::with_callback(capacity, None)
+    }
+
+    pub fn with_eviction_callback<E>(capacity: usize, on_evict: E) -> LruCache<K, V, E>
+    where
+        E: FnMut(K, V),
+    {
+        LruCache::with_callback(capacity, Some(on_evict))
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, E: FnMut(K, V)> LruCache<K, V, E> {
+    fn with_callback(capacity: usize, on_evict: Option<E>) -> LruCache<K, V, E> {
+        LruCache {
+            map: HashMap::with_capacity(capacity),
+            order: DoublyLinkedList::new(),
+            capacity,
+            on_evict,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.map.get(key) {
+            Some(&node) => {
+                self.stats.hits += 1;
+                self.order.move_to_front(node);
+                self.order.get_mut(node).map(|(_, value)| value)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let &node = self.map.get(key)?;
+        self.order.get(node).map(|(_, value)| value)
+    }
+
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        self.order.back().map(|(key, value)| (key, value))
+    }
+
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&node) = self.map.get(&key) {
+            self.order.move_to_front(node);
+            let (_, old) = self.order.get_mut(node)?;
+            return Some(std::mem::replace(old, value));
+        }
+        let node = self.order.push_front_handle((key.clone(), value));
+        self.map.insert(key, node);
+        self.evict_to(self.capacity);
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let node = self.map.remove(key)?;
+        self.order.remove(node).map(|(_, value)| value)
+    }
+
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (key, value) = self.order.pop_back()?;
+        self.map.remove(&key);
+        Some((key, value))
+    }
+
+    pub fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict_to(capacity);
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.order = DoublyLinkedList::new();
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> {
+        self.order.iter().map(|(key, value)| (key, value))
+    }
+
+    fn evict_to(&mut self, capacity: usize) {
+        while self.order.len() > capacity {
+            let (key, value) = self.pop_lru().unwrap();
+            self.stats.evictions += 1;
+            if let Some(on_evict) = self.on_evict.as_mut() {
+                on_evict(key, value);
+            }
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, E> fmt::Debug for LruCache<K, V, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.order.iter().map(|(key, value)| (key, value)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn it_works() {
+        let mut cache = LruCache::new(2);
+        assert_eq!(cache.put("foo", 1), None);
+        assert_eq!(cache.put("bar", 2), None);
+        assert_eq!(cache.get("foo"), Some(&1));
+        assert_eq!(cache.put("baz", 3), None);
+        assert_eq!(cache.get("bar"), None);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.put("foo", 10), Some(1));
+        assert_eq!(cache.put("qux", 4), None);
+        assert!(!cache.contains("baz"));
+        assert_eq!(format!("{:?}", cache), r#"{"qux": 4, "foo": 10}"#);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                evictions: 2
+            }
+        );
+    }
+
+    #[test]
+    fn peek_does_not_touch_recency() {
+        let mut cache = LruCache::new(2);
+        cache.put(1, "one");
+        cache.put(2, "two");
+        assert_eq!(cache.peek(&1), Some(&"one"));
+        assert_eq!(cache.peek_lru(), Some((&1, &"one")));
+        cache.put(3, "three");
+        assert_eq!(cache.peek(&1), None);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                evictions: 1,
+                ..Default::default()
+            }
+        );
+
+        if let Some(value) = cache.get_mut(&2) {
+            *value = "TWO";
+        }
+        assert_eq!(cache.pop_lru(), Some((3, "three")));
+        assert_eq!(cache.pop_lru(), Some((2, "TWO")));
+        assert_eq!(cache.pop_lru(), None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn eviction_callback_and_resize() {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let sink = evicted.clone();
+        let mut cache = LruCache::with_eviction_callback(3, move |key, value| {
+            sink.lock().unwrap().push((key, value))
+        });
+        for i in 0..5 {
+            cache.put(i, i * 10);
+        }
+        assert_eq!(*evicted.lock().unwrap(), vec![(0, 0), (1, 10)]);
+
+        cache.get(&2);
+        cache.resize(1);
+        assert_eq!(cache.capacity(), 1);
+        assert_eq!(
+            *evicted.lock().unwrap(),
+            vec![(0, 0), (1, 10), (3, 30), (4, 40)]
+        );
+        assert_eq!(cache.iter().collect::<Vec<_>>(), vec![(&2, &20)]);
+
+        assert_eq!(cache.remove(&2), Some(20));
+        assert_eq!(cache.remove(&2), None);
+        cache.resize(0);
+        cache.put(7, 70);
+        assert!(cache.is_empty());
+        assert_eq!(evicted.lock().unwrap().last(), Some(&(7, 70)));
+        assert_eq!(cache.stats().evictions, 5);
+    }
+
+    #[test]
+    fn single_threaded_callbacks() {
+        use std::{cell::RefCell, rc::Rc};
+
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let sink = evicted.clone();
+        let mut cache = LruCache::with_eviction_callback(0, move |key, value| {
+            sink.borrow_mut().push((key, value))
+        });
+        assert_eq!(cache.put("a", 1), None);
+        assert_eq!(cache.put("b", 2), None);
+        assert!(cache.is_empty());
+        assert_eq!(*RefCell::borrow(&evicted), vec![("a", 1), ("b", 2)]);
+    }
+
+    #[test]
+    fn matches_reference_model() {
+        use rand::Rng;
+
+        let mut rng = rand::thread_rng();
+        let mut cache = LruCache::new(8);
+        // Most recently used last.
+        let mut model: Vec<(u32, u32)> = Vec::new();
+        for step in 0..5000 {
+            let key = rng.gen_range(0..16);
+            match rng.gen_range(0..4) {
+                0 | 1 => {
+                    let old = model
+                        .iter()
+                        .position(|&(k, _)| k == key)
+                        .map(|i| model.remove(i).1);
+                    model.push((key, step));
+                    if model.len() > 8 {
+                        model.remove(0);
+                    }
+                    assert_eq!(cache.put(key, step), old);
+                }
+                2 => {
+                    let found = model
+                        .iter()
+                        .position(|&(k, _)| k == key)
+                        .map(|i| model.remove(i));
+                    if let Some(entry) = found {
+                        model.push(entry);
+                    }
+                    assert_eq!(cache.get(&key).copied(), found.map(|(_, v)| v));
+                }
+                _ => {
+                    let found = model
+                        .iter()
+                        .position(|&(k, _)| k == key)
+                        .map(|i| model.remove(i).1);
+                    assert_eq!(cache.remove(&key), found);
+                }
+            }
+            let order: Vec<(u32, u32)> = cache.iter().rev().map(|(&k, &v)| (k, v)).collect();
+            assert_eq!(order, model);
+        }
+    }
+
+    #[test]
+    fn string_keys_and_send() {
+        fn assert_send<T: Send>(_: &T) {}
+        let mut cache: LruCache<String, Vec<u8>> = LruCache::new(4);
+        assert_send(&cache);
+        cache.put("a".to_string(), vec![1]);
+        assert_eq!(cache.get("a"), Some(&vec![1]));
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.get("a"), None);
+    }
+}
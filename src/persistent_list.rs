@@ -0,0 +1,286 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
+    sync::Arc,
+};
+
+type Link<T> = Option<Arc<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+}
+
+// An immutable singly linked list: `cons` and `tail` return new lists that share every node
+// after the head with the original, so keeping old versions around costs one node per push.
+pub struct PersistentList<T> {
+    head: Link<T>,
+    len: usize,
+}
+
+pub struct PersistentListIter<'a, T> {
+    next: Option<&'a Node<T>>,
+    len: usize,
+}
+
+impl<T> PersistentList<T> {
+    pub fn new() -> PersistentList<T> {
+        PersistentList { head: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn cons(&self, elem: T) -> PersistentList<T> {
+        PersistentList {
+            head: Some(Arc::new(Node {
+                elem,
+                next: self.head.clone(),
+            })),
+            len: self.len + 1,
+        }
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.elem)
+    }
+
+    pub fn tail(&self) -> PersistentList<T> {
+        match &self.head {
+            Some(node) => PersistentList {
+                head: node.next.clone(),
+                len: self.len - 1,
+            },
+            None => PersistentList::new(),
+        }
+    }
+
+    pub fn ptr_eq(&self, other: &PersistentList<T>) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    pub fn iter(&self) -> PersistentListIter<'_, T> {
+        PersistentListIter {
+            next: self.head.as_deref(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: Clone> PersistentList<T> {
+    pub fn reversed(&self) -> PersistentList<T> {
+        self.iter()
+            .fold(PersistentList::new(), |list, elem| list.cons(elem.clone()))
+    }
+}
+
+impl<T> Drop for PersistentList<T> {
+    fn drop(&mut self) {
+        // Unlink nodes one at a time instead of letting each drop its successor recursively,
+        // stopping at the first one that another list still shares. `into_inner` rather than
+        // `try_unwrap`: when two lists sharing a node are dropped at once, both `try_unwrap`s
+        // can fail and leave the last reference to be dropped recursively.
+        let mut curr = self.head.take();
+        while let Some(node) = curr {
+            match Arc::into_inner(node) {
+                Some(mut node) => curr = node.next.take(),
+                None => break,
+            }
+        }
+    }
+}
+
+impl<T> Default for PersistentList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for PersistentList<T> {
+    fn clone(&self) -> Self {
+        PersistentList {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for PersistentList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for PersistentList<T> {}
+
+impl<T: Hash> Hash for PersistentList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        for elem in self {
+            elem.hash(state);
+        }
+    }
+}
+
+// Collects in iteration order, so the first item becomes the head.
+impl<T> FromIterator<T> for PersistentList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let items: Vec<T> = iter.into_iter().collect();
+        items
+            .into_iter()
+            .rev()
+            .fold(PersistentList::new(), |list, elem| list.cons(elem))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentList<T> {
+    type Item = &'a T;
+
+    type IntoIter = PersistentListIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Clone for PersistentListIter<'a, T> {
+    fn clone(&self) -> Self {
+        PersistentListIter { ..*self }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for PersistentListIter<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a, T> Iterator for PersistentListIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.len -= 1;
+            self.next = node.next.as_deref();
+            &node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for PersistentListIter<'a, T> {}
+
+impl<'a, T> FusedIterator for PersistentListIter<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let empty = PersistentList::new();
+        assert_eq!(empty.head(), None);
+        assert!(empty.tail().is_empty());
+
+        let list = empty.cons(1).cons(2).cons(3);
+        assert_eq!(list.head(), Some(&3));
+        assert_eq!(list.len(), 3);
+        let list = list.tail();
+        assert_eq!(list.head(), Some(&2));
+        let list = list.tail();
+        assert_eq!(list.head(), Some(&1));
+        let list = list.tail();
+        assert_eq!(list.head(), None);
+        assert_eq!(list.len(), 0);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn shares_structure() {
+        let base: PersistentList<i32> = (1..=3).collect();
+        let left = base.cons(10);
+        let right = base.cons(20);
+        assert!(left.tail().ptr_eq(&base));
+        assert!(right.tail().ptr_eq(&left.tail()));
+        assert!(!left.ptr_eq(&right));
+        assert!(base.clone().ptr_eq(&base));
+
+        drop(base);
+        assert_eq!(left.iter().copied().collect::<Vec<_>>(), vec![10, 1, 2, 3]);
+        assert_eq!(right.iter().copied().collect::<Vec<_>>(), vec![20, 1, 2, 3]);
+        assert_eq!(left.tail(), right.tail());
+        assert_eq!(left.reversed().head(), Some(&3));
+    }
+
+    #[test]
+    fn iterators() {
+        let list: PersistentList<i32> = (1..=4).collect();
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(format!("{:?}", iter), "[2, 3, 4]");
+        assert_eq!(iter.by_ref().count(), 3);
+        assert_eq!(iter.next(), None);
+        assert_eq!(format!("{:?}", list), "[1, 2, 3, 4]");
+        assert_eq!((&list).into_iter().sum::<i32>(), 10);
+    }
+
+    #[test]
+    fn long_lists_drop_iteratively() {
+        let mut list = PersistentList::new();
+        for i in 0..1_000_000 {
+            list = list.cons(i);
+        }
+        let shared = list.tail().tail();
+        drop(list);
+        assert_eq!(shared.len(), 999_998);
+        assert_eq!(shared.head(), Some(&999_997));
+    }
+
+    #[test]
+    fn concurrent_drops_stay_iterative() {
+        for _ in 0..8 {
+            // Only the two threads hold the list, so they race to release its last reference.
+            let list: PersistentList<i32> = (0..200_000).collect();
+            let handles: Vec<_> = [list.clone(), list]
+                .into_iter()
+                .map(|list| std::thread::spawn(move || drop(list)))
+                .collect();
+            for handle in handles {
+                handle.join().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        let list: PersistentList<String> = ["a".to_string()].into_iter().collect();
+        assert_send_sync(&list);
+        let handle = {
+            let list = list.clone();
+            std::thread::spawn(move || list.cons("b".to_string()).len())
+        };
+        assert_eq!(handle.join().unwrap(), 2);
+        assert_eq!(list.len(), 1);
+    }
+}
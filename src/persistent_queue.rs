@@ -0,0 +1,759 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+const DEFAULT_SEGMENT_BYTES: u64 = 1 << 20;
+const RECORD_HEADER_BYTES: u64 = 8;
+const CURSOR_ENTRY_BYTES: u64 = 20;
+const MAX_CURSOR_ENTRIES: u64 = 1024;
+const CURSOR_FILE: &str = "cursor";
+const SEGMENT_EXTENSION: &str = "seg";
+
+pub trait Codec: Sized {
+    fn encode(&self, out: &mut Vec<u8>);
+    fn decode(bytes: &[u8]) -> io::Result<Self>;
+}
+
+impl Codec for Vec<u8> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self);
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl Codec for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        String::from_utf8(bytes.to_vec())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+macro_rules! int_codec {
+    ($($int:ty),*) => {
+        $(
+            impl Codec for $int {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(bytes: &[u8]) -> io::Result<Self> {
+                    bytes
+                        .try_into()
+                        .map(<$int>::from_le_bytes)
+                        .map_err(|_| invalid_data("integer record has the wrong length"))
+                }
+            }
+        )*
+    };
+}
+
+int_codec!(u32, u64, i32, i64);
+
+// A file that records or cursor entries are appended to, behind a trait so tests can make
+// writes fail.
+trait LogWriter: Write + Send {
+    fn set_len(&self, len: u64) -> io::Result<()>;
+    fn sync_data(&self) -> io::Result<()>;
+}
+
+impl LogWriter for File {
+    fn set_len(&self, len: u64) -> io::Result<()> {
+        File::set_len(self, len)
+    }
+
+    fn sync_data(&self) -> io::Result<()> {
+        File::sync_data(self)
+    }
+}
+
+// On disk the queue is a directory of segment files named by a sequence number, each holding
+// `[payload length: u32][crc32 of payload: u32][payload]` records, plus a `cursor` file that
+// gets a `[segment: u64][offset: u64][crc32 of both: u32]` entry appended on every dequeue.
+// Recovery trusts the last intact cursor entry and every intact record after it; a torn
+// record at the end of the newest segment (or a torn cursor entry) is cut off. Segments the
+// cursor has moved past are deleted, and the cursor file is rewritten once it grows long.
+//
+// Writes go straight to the files, so they survive the process dying. Call `sync` to also
+// survive the machine dying.
+pub struct PersistentQueue<T> {
+    dir: PathBuf,
+    segment_bytes: u64,
+    // Ids of the segments still on disk, oldest first; the last one is appended to.
+    segments: VecDeque<u64>,
+    writer: Box<dyn LogWriter>,
+    write_offset: u64,
+    // Set when a failed append could not be cut back off the segment.
+    writer_poisoned: bool,
+    // Where every unconsumed record starts, in queue order.
+    records: VecDeque<(u64, u64)>,
+    reader: Option<(u64, File)>,
+    cursor: Box<dyn LogWriter>,
+    cursor_entries: u64,
+    // Set when a failed cursor entry could not be cut back off the log.
+    cursor_poisoned: bool,
+    _boo: PhantomData<T>,
+}
+
+impl<T: Codec> PersistentQueue<T> {
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<PersistentQueue<T>> {
+        PersistentQueue::open_with_segment_size(dir, DEFAULT_SEGMENT_BYTES)
+    }
+
+    pub fn open_with_segment_size(
+        dir: impl AsRef<Path>,
+        segment_bytes: u64,
+    ) -> io::Result<PersistentQueue<T>> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let mut segments = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == SEGMENT_EXTENSION) {
+                let id = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str()?.parse().ok())
+                    .ok_or_else(|| invalid_data("unexpected segment file name"))?;
+                segments.push(id);
+            }
+        }
+        segments.sort_unstable();
+
+        let mut cursor = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(dir.join(CURSOR_FILE))?;
+        let (cursor_entries, position) = read_cursor(&mut cursor)?;
+        let (read_segment, read_offset) = position.unwrap_or((0, 0));
+
+        let mut records = VecDeque::new();
+        let mut write_offset = 0;
+        let mut live = VecDeque::new();
+        for (i, &id) in segments.iter().enumerate() {
+            let path = segment_path(&dir, id);
+            if id < read_segment {
+                fs::remove_file(path)?;
+                continue;
+            }
+            let is_last = i == segments.len() - 1;
+            let start = if id == read_segment { read_offset } else { 0 };
+            let mut file = OpenOptions::new().read(true).write(true).open(&path)?;
+            let (offsets, valid_len) = scan_segment(&mut file)?;
+            if valid_len < file.metadata()?.len() {
+                if !is_last {
+                    return Err(invalid_data("corrupt record in a sealed segment"));
+                }
+                file.set_len(valid_len)?;
+            }
+            records.extend(
+                offsets
+                    .into_iter()
+                    .filter(|&offset| offset >= start)
+                    .map(|offset| (id, offset)),
+            );
+            write_offset = valid_len;
+            live.push_back(id);
+        }
+        if live.is_empty() {
+            live.push_back(read_segment);
+            write_offset = 0;
+        }
+
+        let writer = open_segment_writer(&dir, *live.back().unwrap())?;
+        Ok(PersistentQueue {
+            dir,
+            segment_bytes,
+            segments: live,
+            writer,
+            write_offset,
+            writer_poisoned: false,
+            records,
+            reader: None,
+            cursor: Box::new(cursor),
+            cursor_entries,
+            cursor_poisoned: false,
+            _boo: PhantomData,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn enqueue(&mut self, val: &T) -> io::Result<()> {
+        if self.writer_poisoned {
+            return Err(poisoned());
+        }
+        if self.write_offset >= self.segment_bytes {
+            let id = self.segments.back().unwrap() + 1;
+            self.writer = open_segment_writer(&self.dir, id)?;
+            self.segments.push_back(id);
+            self.write_offset = 0;
+        }
+        let mut record = vec![0; RECORD_HEADER_BYTES as usize];
+        val.encode(&mut record);
+        let payload_len = u32::try_from(record.len() - RECORD_HEADER_BYTES as usize)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "record is too large"))?;
+        let checksum = crc32(&record[RECORD_HEADER_BYTES as usize..]);
+        record[..4].copy_from_slice(&payload_len.to_le_bytes());
+        record[4..8].copy_from_slice(&checksum.to_le_bytes());
+        if let Err(err) = self.writer.write_all(&record) {
+            // Recovery stops at the first broken record, so a partial one left here would
+            // hide everything appended after it. If it can't be cut off, stop appending.
+            if self.writer.set_len(self.write_offset).is_err() {
+                self.writer_poisoned = true;
+            }
+            return Err(err);
+        }
+
+        self.records
+            .push_back((*self.segments.back().unwrap(), self.write_offset));
+        self.write_offset += record.len() as u64;
+        Ok(())
+    }
+
+    pub fn peek(&mut self) -> io::Result<Option<T>> {
+        let Some(&(segment, offset)) = self.records.front() else {
+            return Ok(None);
+        };
+        self.read_record(segment, offset)
+            .and_then(|payload| T::decode(&payload))
+            .map(Some)
+    }
+
+    pub fn deque(&mut self) -> io::Result<Option<T>> {
+        let Some(&(segment, offset)) = self.records.front() else {
+            return Ok(None);
+        };
+        let payload = self.read_record(segment, offset)?;
+        let val = T::decode(&payload)?;
+        self.consume(segment, offset, payload.len())?;
+        Ok(Some(val))
+    }
+
+    // `peek` and `deque` report a record that `T` fails to decode without consuming it, so
+    // this is the way past one. Returns whether there was a record to drop.
+    pub fn skip(&mut self) -> io::Result<bool> {
+        let Some(&(segment, offset)) = self.records.front() else {
+            return Ok(false);
+        };
+        let payload = self.read_record(segment, offset)?;
+        self.consume(segment, offset, payload.len())?;
+        Ok(true)
+    }
+
+    fn consume(&mut self, segment: u64, offset: u64, payload_len: usize) -> io::Result<()> {
+        let next = offset + RECORD_HEADER_BYTES + payload_len as u64;
+        self.commit_cursor(segment, next)?;
+        self.records.pop_front();
+
+        // Drop whole segments once the reader has moved past them. The record is consumed
+        // by now, so a segment that fails to delete is left for the next call to retry.
+        let read_segment = self.records.front().map_or(segment, |&(id, _)| id);
+        while self.segments.len() > 1 && self.segments[0] < read_segment {
+            let id = self.segments[0];
+            if self.reader.as_ref().is_some_and(|(open, _)| *open == id) {
+                self.reader = None;
+            }
+            match fs::remove_file(segment_path(&self.dir, id)) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(_) => break,
+            }
+            self.segments.pop_front();
+        }
+        Ok(())
+    }
+
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.writer.sync_data()?;
+        self.cursor.sync_data()
+    }
+
+    fn read_record(&mut self, segment: u64, offset: u64) -> io::Result<Vec<u8>> {
+        if self
+            .reader
+            .as_ref()
+            .is_none_or(|(open, _)| *open != segment)
+        {
+            self.reader = Some((segment, File::open(segment_path(&self.dir, segment))?));
+        }
+        let file = &mut self.reader.as_mut().unwrap().1;
+        file.seek(SeekFrom::Start(offset))?;
+        read_record(file)?.ok_or_else(|| invalid_data("record changed on disk"))
+    }
+
+    fn commit_cursor(&mut self, segment: u64, offset: u64) -> io::Result<()> {
+        if self.cursor_poisoned {
+            return Err(poisoned());
+        }
+        let entry = cursor_entry(segment, offset);
+        if self.cursor_entries < MAX_CURSOR_ENTRIES {
+            if let Err(err) = self.cursor.write_all(&entry) {
+                // Entries after a torn one would be misaligned and ignored by recovery.
+                if self
+                    .cursor
+                    .set_len(self.cursor_entries * CURSOR_ENTRY_BYTES)
+                    .is_err()
+                {
+                    self.cursor_poisoned = true;
+                }
+                return Err(err);
+            }
+            self.cursor_entries += 1;
+            return Ok(());
+        }
+        // Replace the log with just the latest entry. Syncing the new file before the rename
+        // and the directory after it means even the machine dying leaves a whole log, old or
+        // new, behind.
+        let tmp = self.dir.join(format!("{CURSOR_FILE}.tmp"));
+        let mut file = File::create(&tmp)?;
+        file.write_all(&entry)?;
+        file.sync_data()?;
+        fs::rename(&tmp, self.dir.join(CURSOR_FILE))?;
+        let cursor = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open(self.dir.join(CURSOR_FILE))?;
+        self.cursor = Box::new(cursor);
+        self.cursor_entries = 1;
+        File::open(&self.dir)?.sync_all()
+    }
+}
+
+fn segment_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{id:020}.{SEGMENT_EXTENSION}"))
+}
+
+fn open_segment_writer(dir: &Path, id: u64) -> io::Result<Box<dyn LogWriter>> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(segment_path(dir, id))?;
+    Ok(Box::new(file))
+}
+
+fn poisoned() -> io::Error {
+    io::Error::other("queue is poisoned by a write that could not be rolled back")
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn cursor_entry(segment: u64, offset: u64) -> [u8; CURSOR_ENTRY_BYTES as usize] {
+    let mut entry = [0; CURSOR_ENTRY_BYTES as usize];
+    entry[..8].copy_from_slice(&segment.to_le_bytes());
+    entry[8..16].copy_from_slice(&offset.to_le_bytes());
+    let checksum = crc32(&entry[..16]);
+    entry[16..].copy_from_slice(&checksum.to_le_bytes());
+    entry
+}
+
+// Returns the number of intact entries and the position in the last one, cutting off a torn
+// or corrupt tail.
+fn read_cursor(file: &mut File) -> io::Result<(u64, Option<(u64, u64)>)> {
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let mut position = None;
+    let mut entries = 0;
+    for entry in bytes.chunks(CURSOR_ENTRY_BYTES as usize) {
+        if entry.len() < CURSOR_ENTRY_BYTES as usize
+            || crc32(&entry[..16]).to_le_bytes() != entry[16..]
+        {
+            break;
+        }
+        let segment = u64::from_le_bytes(entry[..8].try_into().unwrap());
+        let offset = u64::from_le_bytes(entry[8..16].try_into().unwrap());
+        position = Some((segment, offset));
+        entries += 1;
+    }
+    if entries * CURSOR_ENTRY_BYTES < bytes.len() as u64 {
+        file.set_len(entries * CURSOR_ENTRY_BYTES)?;
+    }
+    Ok((entries, position))
+}
+
+// Reads one record at the current position. `None` means the record is missing, cut short
+// or fails its checksum.
+fn read_record(file: &mut File) -> io::Result<Option<Vec<u8>>> {
+    let mut header = [0; RECORD_HEADER_BYTES as usize];
+    if !read_full(file, &mut header)? {
+        return Ok(None);
+    }
+    let len = u32::from_le_bytes(header[..4].try_into().unwrap()) as u64;
+    let checksum = u32::from_le_bytes(header[4..].try_into().unwrap());
+    let remaining = file.metadata()?.len() - file.stream_position()?;
+    if len > remaining {
+        return Ok(None);
+    }
+    let mut payload = vec![0; len as usize];
+    if !read_full(file, &mut payload)? || crc32(&payload) != checksum {
+        return Ok(None);
+    }
+    Ok(Some(payload))
+}
+
+fn read_full(file: &mut File, buf: &mut [u8]) -> io::Result<bool> {
+    match file.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+// Returns the offset of every intact record and the length of the intact prefix.
+fn scan_segment(file: &mut File) -> io::Result<(Vec<u64>, u64)> {
+    let mut offsets = Vec::new();
+    let mut offset = 0;
+    file.seek(SeekFrom::Start(0))?;
+    while let Some(payload) = read_record(file)? {
+        offsets.push(offset);
+        offset += RECORD_HEADER_BYTES + payload.len() as u64;
+    }
+    Ok((offsets, offset))
+}
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue_dir(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("pqueue-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        path
+    }
+
+    fn segment_files(dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == SEGMENT_EXTENSION))
+            .collect();
+        files.sort();
+        files
+    }
+
+    // Writes half of the next buffer it is given and then fails, like a full disk would.
+    struct FailingWriter {
+        file: File,
+        fail_next: bool,
+        truncate_fails: bool,
+    }
+
+    impl Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if std::mem::take(&mut self.fail_next) {
+                self.file.write_all(&buf[..buf.len() / 2])?;
+                return Err(io::Error::other("disk full"));
+            }
+            self.file.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.file.flush()
+        }
+    }
+
+    impl LogWriter for FailingWriter {
+        fn set_len(&self, len: u64) -> io::Result<()> {
+            if self.truncate_fails {
+                return Err(io::Error::other("read-only"));
+            }
+            self.file.set_len(len)
+        }
+
+        fn sync_data(&self) -> io::Result<()> {
+            self.file.sync_data()
+        }
+    }
+
+    fn failing_writer(path: &Path, truncate_fails: bool) -> Box<dyn LogWriter> {
+        let file = OpenOptions::new().append(true).open(path).unwrap();
+        Box::new(FailingWriter {
+            file,
+            fail_next: true,
+            truncate_fails,
+        })
+    }
+
+    fn inject_failure<T: Codec>(queue: &mut PersistentQueue<T>, truncate_fails: bool) {
+        let segment = segment_path(&queue.dir, *queue.segments.back().unwrap());
+        queue.writer = failing_writer(&segment, truncate_fails);
+    }
+
+    fn chop(path: &Path, bytes: u64) {
+        let file = OpenOptions::new().write(true).open(path).unwrap();
+        let len = file.metadata().unwrap().len();
+        file.set_len(len - bytes).unwrap();
+    }
+
+    #[test]
+    fn it_works() {
+        let dir = queue_dir("basic");
+        let mut queue = PersistentQueue::open(&dir).unwrap();
+        assert_eq!(queue.deque().unwrap(), None::<String>);
+        queue.enqueue(&"one".to_string()).unwrap();
+        queue.enqueue(&"two".to_string()).unwrap();
+        queue.enqueue(&String::new()).unwrap();
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.peek().unwrap().as_deref(), Some("one"));
+        assert_eq!(queue.deque().unwrap().as_deref(), Some("one"));
+        queue.sync().unwrap();
+        drop(queue);
+
+        let mut queue = PersistentQueue::<String>::open(&dir).unwrap();
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.deque().unwrap().as_deref(), Some("two"));
+        assert_eq!(queue.deque().unwrap().as_deref(), Some(""));
+        assert!(queue.is_empty());
+        drop(queue);
+        assert!(PersistentQueue::<String>::open(&dir).unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn rolls_and_compacts_segments() {
+        let dir = queue_dir("segments");
+        let mut queue = PersistentQueue::open_with_segment_size(&dir, 64).unwrap();
+        for i in 0..100u64 {
+            queue.enqueue(&i).unwrap();
+        }
+        assert!(segment_files(&dir).len() > 10);
+        for i in 0..95 {
+            assert_eq!(queue.deque().unwrap(), Some(i));
+        }
+        assert_eq!(segment_files(&dir).len(), 2);
+        drop(queue);
+
+        let mut queue = PersistentQueue::open_with_segment_size(&dir, 64).unwrap();
+        assert_eq!(queue.len(), 5);
+        queue.enqueue(&100).unwrap();
+        let rest: Vec<u64> = std::iter::from_fn(|| queue.deque().unwrap()).collect();
+        assert_eq!(rest, (95..=100).collect::<Vec<_>>());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn compacts_cursor_log() {
+        let dir = queue_dir("cursor");
+        let mut queue = PersistentQueue::open(&dir).unwrap();
+        for i in 0..3000u32 {
+            queue.enqueue(&i).unwrap();
+            assert_eq!(queue.deque().unwrap(), Some(i));
+        }
+        let cursor_len = fs::metadata(dir.join(CURSOR_FILE)).unwrap().len();
+        assert!(cursor_len <= MAX_CURSOR_ENTRIES * CURSOR_ENTRY_BYTES);
+        queue.enqueue(&7).unwrap();
+        drop(queue);
+        let mut queue = PersistentQueue::<u32>::open(&dir).unwrap();
+        assert_eq!(queue.deque().unwrap(), Some(7));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn recovers_from_torn_writes() {
+        let dir = queue_dir("torn");
+        let mut queue = PersistentQueue::open(&dir).unwrap();
+        for word in ["alpha", "beta", "gamma"] {
+            queue.enqueue(&word.as_bytes().to_vec()).unwrap();
+        }
+        drop(queue);
+
+        // Cut the last record off mid-payload, then mid-header.
+        let segment = segment_files(&dir).pop().unwrap();
+        chop(&segment, 2);
+        let mut queue = PersistentQueue::<Vec<u8>>::open(&dir).unwrap();
+        assert_eq!(queue.len(), 2);
+        queue.enqueue(&b"delta".to_vec()).unwrap();
+        drop(queue);
+        chop(&segment, 5 + RECORD_HEADER_BYTES - 3);
+        let mut queue = PersistentQueue::<Vec<u8>>::open(&dir).unwrap();
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.deque().unwrap(), Some(b"alpha".to_vec()));
+        drop(queue);
+
+        // A torn cursor entry falls back to the previous one, redelivering a record.
+        let mut queue = PersistentQueue::<Vec<u8>>::open(&dir).unwrap();
+        assert_eq!(queue.deque().unwrap(), Some(b"beta".to_vec()));
+        drop(queue);
+        chop(&dir.join(CURSOR_FILE), 1);
+        let mut queue = PersistentQueue::<Vec<u8>>::open(&dir).unwrap();
+        assert_eq!(queue.deque().unwrap(), Some(b"beta".to_vec()));
+        assert_eq!(queue.deque().unwrap(), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_corruption() {
+        let dir = queue_dir("corrupt");
+        let mut queue = PersistentQueue::open_with_segment_size(&dir, 16).unwrap();
+        for i in 0..4i64 {
+            queue.enqueue(&i).unwrap();
+        }
+        drop(queue);
+        let first = segment_files(&dir).remove(0);
+        let mut bytes = fs::read(&first).unwrap();
+        bytes[RECORD_HEADER_BYTES as usize] ^= 0xFF;
+        fs::write(&first, bytes).unwrap();
+        let err = PersistentQueue::<i64>::open_with_segment_size(&dir, 16)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let dir2 = queue_dir("decode");
+        let mut queue = PersistentQueue::open(&dir2).unwrap();
+        queue.enqueue(&vec![0xFF, 0xFE]).unwrap();
+        drop(queue);
+        let mut queue = PersistentQueue::<String>::open(&dir2).unwrap();
+        assert!(queue.deque().is_err());
+        assert_eq!(queue.len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_dir_all(&dir2).unwrap();
+    }
+
+    #[test]
+    fn skips_undecodable_records() {
+        let dir = queue_dir("skip");
+        let mut queue = PersistentQueue::open(&dir).unwrap();
+        queue.enqueue(&b"ok".to_vec()).unwrap();
+        queue.enqueue(&vec![0xFF]).unwrap();
+        queue.enqueue(&b"fine".to_vec()).unwrap();
+        drop(queue);
+
+        let mut queue = PersistentQueue::<String>::open(&dir).unwrap();
+        assert_eq!(queue.deque().unwrap().as_deref(), Some("ok"));
+        assert!(queue.peek().is_err());
+        assert!(queue.deque().is_err());
+        assert!(queue.skip().unwrap());
+        assert_eq!(queue.deque().unwrap().as_deref(), Some("fine"));
+        assert!(!queue.skip().unwrap());
+        drop(queue);
+        assert!(PersistentQueue::<String>::open(&dir).unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_writes_leave_no_partial_record() {
+        let dir = queue_dir("failed-write");
+        let mut queue = PersistentQueue::open(&dir).unwrap();
+        queue.enqueue(&1u64).unwrap();
+        inject_failure(&mut queue, false);
+        assert!(queue.enqueue(&2).is_err());
+        assert_eq!(queue.len(), 1);
+        queue.enqueue(&3).unwrap();
+        assert_eq!(queue.deque().unwrap(), Some(1));
+        drop(queue);
+
+        let mut queue = PersistentQueue::<u64>::open(&dir).unwrap();
+        queue.enqueue(&4).unwrap();
+        let rest: Vec<u64> = std::iter::from_fn(|| queue.deque().unwrap()).collect();
+        assert_eq!(rest, vec![3, 4]);
+
+        // Without a way to cut the partial record off, the queue refuses to append after it.
+        inject_failure(&mut queue, true);
+        assert!(queue.enqueue(&5).is_err());
+        assert!(queue.enqueue(&6).is_err());
+        assert!(queue.is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_cursor_writes_are_rolled_back() {
+        let dir = queue_dir("failed-cursor");
+        let mut queue = PersistentQueue::open(&dir).unwrap();
+        for i in 0..5u32 {
+            queue.enqueue(&i).unwrap();
+        }
+        assert_eq!(queue.deque().unwrap(), Some(0));
+        queue.cursor = failing_writer(&dir.join(CURSOR_FILE), false);
+        assert!(queue.deque().is_err());
+        assert_eq!(queue.len(), 4);
+        assert_eq!(queue.deque().unwrap(), Some(1));
+        assert_eq!(queue.deque().unwrap(), Some(2));
+        drop(queue);
+
+        let mut queue = PersistentQueue::<u32>::open(&dir).unwrap();
+        assert_eq!(queue.deque().unwrap(), Some(3));
+        queue.cursor = failing_writer(&dir.join(CURSOR_FILE), true);
+        assert!(queue.deque().is_err());
+        assert!(queue.deque().is_err());
+        assert_eq!(queue.len(), 1);
+        queue.enqueue(&5).unwrap();
+        drop(queue);
+        let rest: Vec<u32> = {
+            let mut queue = PersistentQueue::<u32>::open(&dir).unwrap();
+            std::iter::from_fn(|| queue.deque().unwrap()).collect()
+        };
+        assert_eq!(rest, vec![4, 5]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_segment_deletes_keep_the_record() {
+        let dir = queue_dir("failed-delete");
+        let mut queue = PersistentQueue::open_with_segment_size(&dir, 16).unwrap();
+        for i in 0..3i64 {
+            queue.enqueue(&i).unwrap();
+        }
+        // Keep the first segment open for reading, then put a directory in its place so
+        // deleting it fails.
+        assert_eq!(queue.peek().unwrap(), Some(0));
+        let first = segment_files(&dir).remove(0);
+        fs::remove_file(&first).unwrap();
+        fs::create_dir(&first).unwrap();
+        assert_eq!(queue.deque().unwrap(), Some(0));
+        assert!(first.is_dir());
+
+        fs::remove_dir(&first).unwrap();
+        assert_eq!(queue.deque().unwrap(), Some(1));
+        assert_eq!(segment_files(&dir).len(), 1);
+        drop(queue);
+        let mut queue = PersistentQueue::<i64>::open_with_segment_size(&dir, 16).unwrap();
+        assert_eq!(queue.deque().unwrap(), Some(2));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,3 +1,5 @@
+use std::{fmt, iter::FusedIterator, marker::PhantomData, mem};
+
 type Link<T> = *mut Node<T>;
 
 struct Node<T> {
@@ -8,19 +10,45 @@ struct Node<T> {
 pub struct Queue<T> {
     head: Link<T>,
     tail: *mut Node<T>,
-    pub length: usize,
+    length: usize,
+    _boo: PhantomData<T>,
+}
+
+pub struct QueueIter<'a, T> {
+    next: Link<T>,
+    len: usize,
+    _boo: PhantomData<&'a T>,
+}
+
+pub struct QueueIterMut<'a, T> {
+    next: Link<T>,
+    len: usize,
+    _boo: PhantomData<&'a mut T>,
+}
+
+pub struct IntoQueueIter<T> {
+    queue: Queue<T>,
 }
 
 impl<T> Queue<T> {
-    fn new() -> Queue<T> {
+    pub fn new() -> Queue<T> {
         Queue {
             head: std::ptr::null_mut(),
             tail: std::ptr::null_mut(),
             length: 0,
+            _boo: PhantomData,
         }
     }
 
-    fn enqueue(&mut self, val: T) {
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    pub fn enqueue(&mut self, val: T) {
         unsafe {
             let new_tail = Box::into_raw(Box::new(Node {
                 value: val,
@@ -37,7 +65,7 @@ impl<T> Queue<T> {
         self.length += 1;
     }
 
-    fn deque(&mut self) -> Option<T> {
+    pub fn deque(&mut self) -> Option<T> {
         unsafe {
             if self.head.is_null() {
                 None
@@ -54,14 +82,209 @@ impl<T> Queue<T> {
         }
     }
 
-    fn peek(&self) -> Option<&T> {
+    pub fn peek(&self) -> Option<&T> {
         unsafe { self.head.as_ref().map(|node| &node.value) }
     }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.head.as_mut().map(|node| &mut node.value) }
+    }
+
+    pub fn clear(&mut self) {
+        while self.deque().is_some() {}
+    }
+
+    pub fn iter(&self) -> QueueIter<'_, T> {
+        QueueIter {
+            next: self.head,
+            len: self.length,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> QueueIterMut<'_, T> {
+        QueueIterMut {
+            next: self.head,
+            len: self.length,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn into_iter(self) -> IntoQueueIter<T> {
+        IntoQueueIter { queue: self }
+    }
+}
+
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        // Keeps freeing the remaining nodes if dropping a value panics.
+        struct DropGuard<'a, T>(&'a mut Queue<T>);
+
+        impl<'a, T> Drop for DropGuard<'a, T> {
+            fn drop(&mut self) {
+                while self.0.deque().is_some() {}
+            }
+        }
+
+        let guard = DropGuard(self);
+        while guard.0.deque().is_some() {}
+        mem::forget(guard);
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for Queue<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Queue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T> FromIterator<T> for Queue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut queue = Queue::new();
+        queue.extend(iter);
+        queue
+    }
+}
+
+impl<T> Extend<T> for Queue<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.enqueue(val);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a> Extend<&'a T> for Queue<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+// `head` and `tail` are the only ways into the chain, every node is freed exactly once by
+// `deque` or drop, and `peek` through `&self` gives out `&T` only. The queue thus carries its
+// `T`s across threads the way a `Vec<T>` would.
+unsafe impl<T: Send> Send for Queue<T> {}
+unsafe impl<T: Sync> Sync for Queue<T> {}
+
+// The iterators walk a chain the queue cannot change while they borrow it, yielding `&'a T`
+// and disjoint `&'a mut T` respectively.
+unsafe impl<'a, T: Sync> Send for QueueIter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for QueueIter<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for QueueIterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for QueueIterMut<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a Queue<T> {
+    type Item = &'a T;
+
+    type IntoIter = QueueIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Clone for QueueIter<'a, T> {
+    fn clone(&self) -> Self {
+        QueueIter { ..*self }
+    }
+}
+
+impl<'a, T> Iterator for QueueIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            self.next.as_ref().map(|node| {
+                self.len -= 1;
+                self.next = node.next;
+                &node.value
+            })
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Queue<T> {
+    type IntoIter = QueueIterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for QueueIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            self.next.as_mut().map(|node| {
+                self.len -= 1;
+                self.next = node.next;
+                &mut node.value
+            })
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> IntoIterator for Queue<T> {
+    type IntoIter = IntoQueueIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_iter()
+    }
+}
+
+impl<T> Iterator for IntoQueueIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.queue.deque()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.queue.length, Some(self.queue.length))
+    }
 }
 
+impl<'a, T> ExactSizeIterator for QueueIter<'a, T> {}
+
+impl<'a, T> ExactSizeIterator for QueueIterMut<'a, T> {}
+
+impl<T> ExactSizeIterator for IntoQueueIter<T> {}
+
+impl<'a, T> FusedIterator for QueueIter<'a, T> {}
+
+impl<'a, T> FusedIterator for QueueIterMut<'a, T> {}
+
+impl<T> FusedIterator for IntoQueueIter<T> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_fixtures::{DropCounter, PanicOnDrop};
+    use std::cell::Cell;
 
     #[test]
     fn it_works() {
@@ -72,7 +295,7 @@ mod tests {
         list.enqueue(9);
 
         assert_eq!(list.deque(), Some(5));
-        assert_eq!(list.length, 2);
+        assert_eq!(list.len(), 2);
 
         list.enqueue(11);
 
@@ -81,10 +304,83 @@ mod tests {
         assert_eq!(list.peek(), Some(&11));
         assert_eq!(list.deque(), Some(11));
         assert_eq!(list.deque(), None);
-        assert_eq!(list.length, 0);
+        assert_eq!(list.len(), 0);
 
         list.enqueue(69);
         assert_eq!(list.peek(), Some(&69));
-        assert_eq!(list.length, 1);
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    fn container_api() {
+        let mut queue: Queue<i32> = (1..=3).collect();
+        queue.extend(&[4, 5]);
+        assert_eq!(format!("{:?}", queue), "[1, 2, 3, 4, 5]");
+        *queue.peek_mut().unwrap() = 10;
+        for val in &mut queue {
+            *val += 1;
+        }
+        let mut iter = queue.iter();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next(), Some(&11));
+        assert_eq!(iter.copied().collect::<Vec<_>>(), vec![3, 4, 5, 6]);
+
+        let copy = queue.clone();
+        assert_eq!(queue.deque(), Some(11));
+        assert_eq!(copy.len(), 5);
+        assert_eq!(copy.into_iter().collect::<Vec<_>>(), vec![11, 3, 4, 5, 6]);
+
+        queue.clear();
+        assert!(queue.is_empty());
+        assert_eq!(queue.peek(), None);
+        queue.enqueue(1);
+        assert_eq!(queue.iter().collect::<Vec<_>>(), vec![&1]);
+        assert!(Queue::<i32>::default().is_empty());
+    }
+
+    #[test]
+    fn does_not_leak() {
+        let drops = Cell::new(0);
+        let mut queue = Queue::new();
+        for _ in 0..10 {
+            queue.enqueue(DropCounter(&drops));
+        }
+        drop(queue.deque());
+        assert_eq!(drops.get(), 1);
+        drop(queue);
+        assert_eq!(drops.get(), 10);
+
+        let drops = Cell::new(0);
+        let mut queue: Queue<_> = (0..10).map(|_| DropCounter(&drops)).collect();
+        queue.clear();
+        assert_eq!(drops.get(), 10);
+        queue.extend((0..5).map(|_| DropCounter(&drops)));
+        let mut iter = queue.into_iter();
+        drop(iter.next());
+        assert_eq!(drops.get(), 11);
+        drop(iter);
+        assert_eq!(drops.get(), 15);
+    }
+
+    #[test]
+    fn drop_continues_after_panic() {
+        let drops = Cell::new(0);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut queue = Queue::new();
+            queue.enqueue(PanicOnDrop(&drops, false));
+            queue.enqueue(PanicOnDrop(&drops, true));
+            queue.enqueue(PanicOnDrop(&drops, false));
+        }));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 3);
+    }
+
+    #[test]
+    fn long_queue_drops_without_recursion() {
+        let mut queue = Queue::new();
+        for i in 0..1_000_000 {
+            queue.enqueue(i);
+        }
+        drop(queue);
     }
 }
@@ -20,7 +20,7 @@ fn partition(arr: &mut [i32], lo: usize, hi: usize) -> usize {
     arr[idx] = pivot;
     idx
 }
-fn quick_sort(arr: &mut Vec<i32>) {
+fn quick_sort(arr: &mut [i32]) {
     let len = arr.len();
     qs(arr, 0, len - 1);
 }
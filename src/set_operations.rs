@@ -0,0 +1,245 @@
+use std::cmp::Ordering;
+
+use crate::binary_search::lower_bound;
+
+// Galloping only pays off once one side is much larger than the other; below this
+// size ratio a linear merge touches fewer elements.
+const GALLOP_RATIO: usize = 16;
+
+fn gallop<T: Ord>(haystack: &[T], needle: &T) -> usize {
+    let mut hi = 1;
+    while hi < haystack.len() && haystack[hi - 1] < *needle {
+        hi *= 2;
+    }
+    let lo = hi / 2;
+    lo + lower_bound(&haystack[lo..hi.min(haystack.len())], needle)
+}
+
+fn should_gallop(small: usize, large: usize) -> bool {
+    small.saturating_mul(GALLOP_RATIO) < large
+}
+
+pub fn union<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut result = Vec::with_capacity(a.len().max(b.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            Ordering::Less => {
+                result.push(a[i].clone());
+                i += 1;
+            }
+            Ordering::Greater => {
+                result.push(b[j].clone());
+                j += 1;
+            }
+            Ordering::Equal => {
+                result.push(a[i].clone());
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    result.extend_from_slice(&a[i..]);
+    result.extend_from_slice(&b[j..]);
+    result
+}
+
+pub fn intersection<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let (small, large) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if should_gallop(small.len(), large.len()) {
+        let mut result = Vec::new();
+        let mut base = 0;
+        for item in small {
+            base += gallop(&large[base..], item);
+            if base == large.len() {
+                break;
+            }
+            if large[base] == *item {
+                result.push(item.clone());
+                base += 1;
+            }
+        }
+        return result;
+    }
+
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                result.push(a[i].clone());
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    result
+}
+
+pub fn difference<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    if should_gallop(a.len(), b.len()) {
+        let mut result = Vec::new();
+        let mut base = 0;
+        for item in a {
+            base += gallop(&b[base..], item);
+            if base < b.len() && b[base] == *item {
+                base += 1;
+            } else {
+                result.push(item.clone());
+            }
+        }
+        return result;
+    }
+
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            Ordering::Less => {
+                result.push(a[i].clone());
+                i += 1;
+            }
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    result.extend_from_slice(&a[i..]);
+    result
+}
+
+pub fn symmetric_difference<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            Ordering::Less => {
+                result.push(a[i].clone());
+                i += 1;
+            }
+            Ordering::Greater => {
+                result.push(b[j].clone());
+                j += 1;
+            }
+            Ordering::Equal => {
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    result.extend_from_slice(&a[i..]);
+    result.extend_from_slice(&b[j..]);
+    result
+}
+
+pub fn intersection_all<T: Ord + Clone>(lists: &[&[T]]) -> Vec<T> {
+    let mut by_len = lists.to_vec();
+    by_len.sort_by_key(|list| list.len());
+    let Some((smallest, rest)) = by_len.split_first() else {
+        return Vec::new();
+    };
+    let mut result = smallest.to_vec();
+    for list in rest {
+        if result.is_empty() {
+            break;
+        }
+        result = intersection(&result, list);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use std::collections::BTreeSet;
+
+    fn random_set(rng: &mut impl Rng, len: usize, max: u32) -> Vec<u32> {
+        let set: BTreeSet<u32> = (0..len).map(|_| rng.gen_range(0..max)).collect();
+        set.into_iter().collect()
+    }
+
+    #[test]
+    fn it_works() {
+        let a = [1, 3, 5, 7, 9, 11];
+        let b = [3, 4, 5, 6, 11, 12];
+        assert_eq!(union(&a, &b), vec![1, 3, 4, 5, 6, 7, 9, 11, 12]);
+        assert_eq!(intersection(&a, &b), vec![3, 5, 11]);
+        assert_eq!(difference(&a, &b), vec![1, 7, 9]);
+        assert_eq!(difference(&b, &a), vec![4, 6, 12]);
+        assert_eq!(symmetric_difference(&a, &b), vec![1, 4, 6, 7, 9, 12]);
+        assert_eq!(
+            intersection_all(&[&a[..], &b[..], &[0, 5, 11, 20]]),
+            vec![5, 11]
+        );
+        assert_eq!(intersection_all::<u32>(&[]), Vec::<u32>::new());
+        assert_eq!(union::<u32>(&[], &[]), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn multisets() {
+        let a = [1, 1, 2, 2, 2, 3];
+        let b = [1, 2, 2, 4];
+        assert_eq!(union(&a, &b), vec![1, 1, 2, 2, 2, 3, 4]);
+        assert_eq!(intersection(&a, &b), vec![1, 2, 2]);
+        assert_eq!(difference(&a, &b), vec![1, 2, 3]);
+        assert_eq!(symmetric_difference(&a, &b), vec![1, 2, 3, 4]);
+
+        let large: Vec<u32> = (0..1000).flat_map(|x| [x, x]).collect();
+        assert_eq!(intersection(&[5, 5, 5, 7], &large), vec![5, 5, 7]);
+        assert_eq!(difference(&[5, 5, 5, 7], &large), vec![5]);
+    }
+
+    #[test]
+    fn matches_btreeset() {
+        let mut rng = rand::thread_rng();
+        for (a_len, b_len) in [
+            (0, 10),
+            (10, 10),
+            (5, 2000),
+            (2000, 5),
+            (300, 700),
+            (1, 5000),
+        ] {
+            let a = random_set(&mut rng, a_len, 10_000);
+            let b = random_set(&mut rng, b_len, 10_000);
+            let (set_a, set_b): (BTreeSet<u32>, BTreeSet<u32>) =
+                (a.iter().copied().collect(), b.iter().copied().collect());
+            let expect = |items: Vec<&u32>| items.into_iter().copied().collect::<Vec<_>>();
+
+            assert_eq!(union(&a, &b), expect(set_a.union(&set_b).collect()));
+            assert_eq!(
+                intersection(&a, &b),
+                expect(set_a.intersection(&set_b).collect())
+            );
+            assert_eq!(
+                difference(&a, &b),
+                expect(set_a.difference(&set_b).collect())
+            );
+            assert_eq!(
+                difference(&b, &a),
+                expect(set_b.difference(&set_a).collect())
+            );
+            assert_eq!(
+                symmetric_difference(&a, &b),
+                expect(set_a.symmetric_difference(&set_b).collect())
+            );
+        }
+
+        let lists: Vec<Vec<u32>> = [4000, 50, 4000, 800]
+            .iter()
+            .map(|&len| random_set(&mut rng, len, 5000))
+            .collect();
+        let slices: Vec<&[u32]> = lists.iter().map(Vec::as_slice).collect();
+        let expected: Vec<u32> = lists[0]
+            .iter()
+            .copied()
+            .filter(|x| lists.iter().all(|list| list.contains(x)))
+            .collect();
+        assert_eq!(intersection_all(&slices), expected);
+    }
+}
@@ -0,0 +1,486 @@
+use std::{
+    borrow::Borrow,
+    fmt,
+    iter::FusedIterator,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+    ptr::NonNull,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const MAX_LEVEL: usize = 32;
+
+type Link<K, V> = Option<NonNull<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    next: Vec<Link<K, V>>,
+}
+
+// `head` holds the first node of every level in use; level 0 links every node in key order
+// and each level above skips over roughly half of the one below it. A predecessor of `None`
+// in the helpers below stands for the head itself.
+pub struct SkipList<K, V> {
+    head: Vec<Link<K, V>>,
+    len: usize,
+    rng: StdRng,
+    _boo: PhantomData<Box<Node<K, V>>>,
+}
+
+pub struct SkipListIter<'a, K, V> {
+    next: Link<K, V>,
+    end: Link<K, V>,
+    _boo: PhantomData<&'a Node<K, V>>,
+}
+
+impl<K, V> SkipList<K, V> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        let mut curr = self.head.first().copied().flatten();
+        while let Some(node) = curr {
+            let node = unsafe { Box::from_raw(node.as_ptr()) };
+            curr = node.next[0];
+        }
+        self.head.clear();
+        self.len = 0;
+    }
+
+    pub fn iter(&self) -> SkipListIter<'_, K, V> {
+        SkipListIter {
+            next: self.head.first().copied().flatten(),
+            end: None,
+            _boo: PhantomData,
+        }
+    }
+}
+
+impl<K: Ord, V> SkipList<K, V> {
+    pub fn new() -> SkipList<K, V> {
+        SkipList::with_rng(StdRng::from_entropy())
+    }
+
+    pub fn with_seed(seed: u64) -> SkipList<K, V> {
+        SkipList::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(rng: StdRng) -> SkipList<K, V> {
+        SkipList {
+            head: Vec::new(),
+            len: 0,
+            rng,
+            _boo: PhantomData,
+        }
+    }
+
+    fn random_level(&mut self) -> usize {
+        (self.rng.gen::<u32>().trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+
+    fn next_of(&self, pred: Link<K, V>, level: usize) -> Link<K, V> {
+        match pred {
+            Some(node) => unsafe { (&(*node.as_ptr()).next)[level] },
+            None => self.head[level],
+        }
+    }
+
+    fn set_next(&mut self, pred: Link<K, V>, level: usize, link: Link<K, V>) {
+        match pred {
+            Some(node) => unsafe { (&mut (*node.as_ptr()).next)[level] = link },
+            None => self.head[level] = link,
+        }
+    }
+
+    // Returns the last node on every level whose key satisfies `before`, which must hold for
+    // a prefix of the keys.
+    fn predecessors(&self, mut before: impl FnMut(&K) -> bool) -> [Link<K, V>; MAX_LEVEL] {
+        let mut preds = [None; MAX_LEVEL];
+        let mut pred = None;
+        for level in (0..self.head.len()).rev() {
+            while let Some(next) = self.next_of(pred, level) {
+                if !before(unsafe { &(*next.as_ptr()).key }) {
+                    break;
+                }
+                pred = Some(next);
+            }
+            preds[level] = pred;
+        }
+        preds
+    }
+
+    fn seek(&self, before: impl FnMut(&K) -> bool) -> Link<K, V> {
+        if self.head.is_empty() {
+            return None;
+        }
+        self.next_of(self.predecessors(before)[0], 0)
+    }
+
+    fn find<Q>(&self, key: &Q) -> Link<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.seek(|k| k.borrow() < key)
+            .filter(|node| unsafe { (*node.as_ptr()).key.borrow() == key })
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key)
+            .map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key)
+            .map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut preds = self.predecessors(|k| *k < key);
+        if let Some(node) = self.head.first().and_then(|_| self.next_of(preds[0], 0)) {
+            unsafe {
+                if (*node.as_ptr()).key == key {
+                    return Some(std::mem::replace(&mut (*node.as_ptr()).value, value));
+                }
+            }
+        }
+        let height = self.random_level();
+        while self.head.len() < height {
+            preds[self.head.len()] = None;
+            self.head.push(None);
+        }
+        let node = unsafe {
+            NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                key,
+                value,
+                next: vec![None; height],
+            })))
+        };
+        for (level, &pred) in preds.iter().enumerate().take(height) {
+            let next = self.next_of(pred, level);
+            unsafe { (&mut (*node.as_ptr()).next)[level] = next };
+            self.set_next(pred, level, Some(node));
+        }
+        self.len += 1;
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if self.head.is_empty() {
+            return None;
+        }
+        let preds = self.predecessors(|k| k.borrow() < key);
+        let node = self.next_of(preds[0], 0)?;
+        unsafe {
+            if (*node.as_ptr()).key.borrow() != key {
+                return None;
+            }
+            let node = Box::from_raw(node.as_ptr());
+            for (level, &next) in node.next.iter().enumerate() {
+                self.set_next(preds[level], level, next);
+            }
+            while self.head.last() == Some(&None) {
+                self.head.pop();
+            }
+            self.len -= 1;
+            Some((node.key, node.value))
+        }
+    }
+
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let node = *self.head.first()?;
+        node.map(|node| unsafe { (&(*node.as_ptr()).key, &(*node.as_ptr()).value) })
+    }
+
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let node = self.predecessors(|_| true)[0]?;
+        unsafe { Some((&(*node.as_ptr()).key, &(*node.as_ptr()).value)) }
+    }
+
+    // Yields nothing if the start bound lies after the end bound.
+    pub fn range<Q, R>(&self, range: R) -> SkipListIter<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let next = match range.start_bound() {
+            Bound::Included(start) => self.seek(|k| k.borrow() < start),
+            Bound::Excluded(start) => self.seek(|k| k.borrow() <= start),
+            Bound::Unbounded => self.head.first().copied().flatten(),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.seek(|k| k.borrow() <= end),
+            Bound::Excluded(end) => self.seek(|k| k.borrow() < end),
+            Bound::Unbounded => None,
+        };
+        let empty = match (next, end) {
+            (Some(next), Some(end)) => unsafe { (*next.as_ptr()).key > (*end.as_ptr()).key },
+            _ => false,
+        };
+        SkipListIter {
+            next: if empty { end } else { next },
+            end,
+            _boo: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+impl<K: Ord, V> SkipList<K, V> {
+    fn assert_invariants(&self) {
+        assert!(self.head.len() <= MAX_LEVEL);
+        assert_ne!(self.head.last(), Some(&None));
+        unsafe {
+            let mut count = 0;
+            let mut curr = self.head.first().copied().flatten();
+            while let Some(node) = curr {
+                count += 1;
+                curr = (&(*node.as_ptr()).next)[0];
+                if let Some(next) = curr {
+                    assert!((*node.as_ptr()).key < (*next.as_ptr()).key);
+                }
+            }
+            assert_eq!(count, self.len);
+            // Every level must be an ordered subsequence of the one below it.
+            for level in 1..self.head.len() {
+                let mut below = self.head[level - 1];
+                let mut curr = self.head[level];
+                while let Some(node) = curr {
+                    assert!((*node.as_ptr()).next.len() > level);
+                    while below != Some(node) {
+                        below = (&(*below.expect("level is not a subsequence").as_ptr()).next)
+                            [level - 1];
+                    }
+                    curr = (&(*node.as_ptr()).next)[level];
+                }
+            }
+        }
+    }
+}
+
+impl<K, V> Drop for SkipList<K, V> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<K: Ord, V> Default for SkipList<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Clone for SkipList<K, V> {
+    fn clone(&self) -> Self {
+        self.iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for SkipList<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipList<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut list = SkipList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipList<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+// A node is linked from several towers at once, but all of those links belong to this list,
+// and only `&mut self` methods relink or free nodes. Shared access reads keys and values.
+unsafe impl<K: Send, V: Send> Send for SkipList<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for SkipList<K, V> {}
+
+// Follows level-0 links of a list borrowed for `'a`, yielding `(&'a K, &'a V)`.
+unsafe impl<'a, K: Sync, V: Sync> Send for SkipListIter<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for SkipListIter<'a, K, V> {}
+
+impl<'a, K, V> IntoIterator for &'a SkipList<K, V> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = SkipListIter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> Clone for SkipListIter<'a, K, V> {
+    fn clone(&self) -> Self {
+        SkipListIter { ..*self }
+    }
+}
+
+impl<'a, K, V> Iterator for SkipListIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next.filter(|&node| Some(node) != self.end)?;
+        unsafe {
+            self.next = (&(*node.as_ptr()).next)[0];
+            Some((&(*node.as_ptr()).key, &(*node.as_ptr()).value))
+        }
+    }
+}
+
+impl<'a, K, V> FusedIterator for SkipListIter<'a, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::DropCounter;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn it_works() {
+        let mut list = SkipList::new();
+        assert_eq!(list.get(&1), None);
+        assert_eq!(list.remove(&1), None);
+        assert_eq!(list.insert(3, "c"), None);
+        assert_eq!(list.insert(1, "a"), None);
+        assert_eq!(list.insert(2, "b"), None);
+        assert_eq!(list.insert(2, "B"), Some("b"));
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.get(&2), Some(&"B"));
+        *list.get_mut(&3).unwrap() = "C";
+        assert_eq!(format!("{:?}", list), r#"{1: "a", 2: "B", 3: "C"}"#);
+        assert_eq!(list.first_key_value(), Some((&1, &"a")));
+        assert_eq!(list.last_key_value(), Some((&3, &"C")));
+
+        assert_eq!(list.remove(&1), Some("a"));
+        assert!(!list.contains_key(&1));
+        assert_eq!(list.remove_entry(&3), Some((3, "C")));
+        assert_eq!(list.last_key_value(), Some((&2, &"B")));
+        list.clear();
+        assert!(list.is_empty());
+        assert_eq!(list.first_key_value(), None);
+        assert_eq!(list.last_key_value(), None);
+        list.assert_invariants();
+    }
+
+    #[test]
+    fn ranges() {
+        let list: SkipList<i32, ()> = (0..20).map(|i| (i * 2, ())).collect();
+        let keys = |iter: SkipListIter<'_, i32, ()>| iter.map(|(&k, _)| k).collect::<Vec<_>>();
+        assert_eq!(keys(list.range(3..9)), vec![4, 6, 8]);
+        assert_eq!(keys(list.range(4..=8)), vec![4, 6, 8]);
+        assert_eq!(keys(list.range(35..)), vec![36, 38]);
+        assert_eq!(keys(list.range(..3)), vec![0, 2]);
+        assert_eq!(keys(list.range(..)).len(), 20);
+        assert_eq!(
+            keys(list.range((Bound::Excluded(4), Bound::Excluded(10)))),
+            vec![6, 8]
+        );
+        assert!(keys(list.range(5..5)).is_empty());
+        assert!(keys(list.range((Bound::Included(9), Bound::Excluded(3)))).is_empty());
+        assert!(keys(list.range(100..)).is_empty());
+
+        let words: SkipList<String, usize> = ["pear", "apple", "fig", "plum"]
+            .iter()
+            .map(|w| (w.to_string(), w.len()))
+            .collect();
+        let range: Vec<&str> = words
+            .range::<str, _>((Bound::Included("b"), Bound::Excluded("pl")))
+            .map(|(k, _)| k.as_str())
+            .collect();
+        assert_eq!(range, vec!["fig", "pear"]);
+        assert_eq!(words.get("plum"), Some(&4));
+    }
+
+    #[test]
+    fn matches_btreemap() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut list = SkipList::with_seed(7);
+        let mut model = BTreeMap::new();
+        for step in 0..20_000 {
+            let key = rng.gen_range(0..500);
+            match rng.gen_range(0..4) {
+                0 | 1 => assert_eq!(list.insert(key, step), model.insert(key, step)),
+                2 => assert_eq!(list.remove(&key), model.remove(&key)),
+                _ => {
+                    let end = key + rng.gen_range(0..40);
+                    assert!(list
+                        .range(key..end)
+                        .map(|(&k, &v)| (k, v))
+                        .eq(model.range(key..end).map(|(&k, &v)| (k, v))));
+                }
+            }
+            assert_eq!(list.get(&key), model.get(&key));
+            assert_eq!(list.len(), model.len());
+            if step % 1000 == 0 {
+                list.assert_invariants();
+            }
+        }
+        list.assert_invariants();
+        assert!(list.iter().eq(model.iter()));
+        assert_eq!(list.first_key_value(), model.first_key_value());
+        assert_eq!(list.last_key_value(), model.last_key_value());
+        assert!(list.clone().iter().eq(model.iter()));
+    }
+
+    #[test]
+    fn stays_shallow_and_drops_everything() {
+        let drops = std::cell::Cell::new(0);
+        let mut list = SkipList::with_seed(1);
+        for i in 0..100_000 {
+            list.insert(i, DropCounter(&drops));
+        }
+        assert!(list.head.len() <= 30);
+        list.insert(5, DropCounter(&drops));
+        assert_eq!(drops.get(), 1);
+        drop(list.remove(&6));
+        assert_eq!(drops.get(), 2);
+        drop(list);
+        assert_eq!(drops.get(), 100_001);
+    }
+}
@@ -0,0 +1,152 @@
+const RK_BASE: u64 = 256;
+const RK_MODULUS: u64 = 1_000_000_007;
+
+fn empty_needle_matches(haystack: &[u8]) -> Vec<usize> {
+    (0..=haystack.len()).collect()
+}
+
+fn prefix_table(needle: &[u8]) -> Vec<usize> {
+    let mut table = vec![0; needle.len()];
+    let mut len = 0;
+    for i in 1..needle.len() {
+        while len > 0 && needle[i] != needle[len] {
+            len = table[len - 1];
+        }
+        if needle[i] == needle[len] {
+            len += 1;
+        }
+        table[i] = len;
+    }
+    table
+}
+
+pub fn kmp_search(haystack: impl AsRef<[u8]>, needle: impl AsRef<[u8]>) -> Vec<usize> {
+    let (haystack, needle) = (haystack.as_ref(), needle.as_ref());
+    if needle.is_empty() {
+        return empty_needle_matches(haystack);
+    }
+
+    let table = prefix_table(needle);
+    let mut matches = Vec::new();
+    let mut matched = 0;
+    for (i, &byte) in haystack.iter().enumerate() {
+        while matched > 0 && byte != needle[matched] {
+            matched = table[matched - 1];
+        }
+        if byte == needle[matched] {
+            matched += 1;
+        }
+        if matched == needle.len() {
+            matches.push(i + 1 - matched);
+            matched = table[matched - 1];
+        }
+    }
+    matches
+}
+
+pub fn horspool_search(haystack: impl AsRef<[u8]>, needle: impl AsRef<[u8]>) -> Vec<usize> {
+    let (haystack, needle) = (haystack.as_ref(), needle.as_ref());
+    if needle.is_empty() {
+        return empty_needle_matches(haystack);
+    }
+
+    let last = needle.len() - 1;
+    let mut shifts = [needle.len(); 256];
+    for (i, &byte) in needle[..last].iter().enumerate() {
+        shifts[byte as usize] = last - i;
+    }
+
+    let mut matches = Vec::new();
+    let mut start = 0;
+    while start + needle.len() <= haystack.len() {
+        let window = &haystack[start..start + needle.len()];
+        if window == needle {
+            matches.push(start);
+        }
+        start += shifts[window[last] as usize];
+    }
+    matches
+}
+
+pub fn rabin_karp_search(haystack: impl AsRef<[u8]>, needle: impl AsRef<[u8]>) -> Vec<usize> {
+    let (haystack, needle) = (haystack.as_ref(), needle.as_ref());
+    if needle.is_empty() {
+        return empty_needle_matches(haystack);
+    }
+    if needle.len() > haystack.len() {
+        return Vec::new();
+    }
+
+    let hash = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .fold(0, |acc, &byte| (acc * RK_BASE + byte as u64) % RK_MODULUS)
+    };
+    // Weight of the byte leaving the window, i.e. BASE^(len - 1).
+    let high = (1..needle.len()).fold(1, |acc, _| acc * RK_BASE % RK_MODULUS);
+
+    let target = hash(needle);
+    let mut rolling = hash(&haystack[..needle.len()]);
+    let mut matches = Vec::new();
+    for start in 0..=haystack.len() - needle.len() {
+        if rolling == target && &haystack[start..start + needle.len()] == needle {
+            matches.push(start);
+        }
+        if let Some(&incoming) = haystack.get(start + needle.len()) {
+            let outgoing = haystack[start] as u64 * high % RK_MODULUS;
+            rolling = (rolling + RK_MODULUS - outgoing) % RK_MODULUS;
+            rolling = (rolling * RK_BASE + incoming as u64) % RK_MODULUS;
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_search(haystack: &[u8], needle: &[u8]) -> Vec<usize> {
+        (0..=haystack.len())
+            .filter(|&i| haystack[i..].starts_with(needle))
+            .collect()
+    }
+
+    #[test]
+    fn it_works() {
+        let haystack = "GET /index.html 200\nGET /about.html 404\nPOST /index.html 200";
+        let searches: [fn(&str, &str) -> Vec<usize>; 3] = [
+            |h, n| kmp_search(h, n),
+            |h, n| horspool_search(h, n),
+            |h, n| rabin_karp_search(h, n),
+        ];
+        for search in searches {
+            assert_eq!(search(haystack, "index.html"), vec![5, 46]);
+            assert_eq!(search(haystack, "GET"), vec![0, 20]);
+            assert_eq!(search(haystack, "500"), Vec::<usize>::new());
+            assert_eq!(search("aaaa", "aa"), vec![0, 1, 2]);
+            assert_eq!(search("abc", ""), vec![0, 1, 2, 3]);
+            assert_eq!(search("ab", "abc"), Vec::<usize>::new());
+        }
+    }
+
+    #[test]
+    fn matches_naive_search() {
+        let haystack = b"abababcabababcababcabcabababababcab\xff\x00\xff\x00\xff";
+        let needles: [&[u8]; 8] = [
+            b"ab",
+            b"abab",
+            b"ababc",
+            b"cab",
+            b"abcabc",
+            b"\xff\x00\xff",
+            b"b",
+            b"abababcabababcababcabcabababababcab\xff\x00\xff\x00\xff",
+        ];
+        for needle in needles {
+            let expected = naive_search(haystack, needle);
+            assert_eq!(kmp_search(haystack, needle), expected);
+            assert_eq!(horspool_search(haystack, needle), expected);
+            assert_eq!(rabin_karp_search(haystack, needle), expected);
+        }
+    }
+}
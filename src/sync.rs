@@ -0,0 +1,12 @@
+mod async_queue;
+mod channel;
+#[cfg(test)]
+mod executor;
+mod hazard;
+mod lock_free_queue;
+
+pub use async_queue::{AsyncQueue, Dequeue};
+pub use channel::{
+    Channel, Receiver, RecvError, RecvTimeoutError, SendError, Sender, TryRecvError, TrySendError,
+};
+pub use lock_free_queue::LockFreeQueue;
@@ -0,0 +1,306 @@
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll, Waker},
+};
+
+use crate::{
+    linked_list::{DoublyLinkedList, NodeHandle},
+    queue::Queue,
+};
+
+// Pending `dequeue` futures park their wakers in `waiters`, oldest first. Each enqueue pops
+// and wakes one of them; the handle lets a future find (and cancel) its own entry in O(1).
+pub struct AsyncQueue<T> {
+    state: Mutex<State<T>>,
+}
+
+struct State<T> {
+    queue: Queue<T>,
+    waiters: DoublyLinkedList<Waker>,
+}
+
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Dequeue<'a, T> {
+    queue: &'a AsyncQueue<T>,
+    waiter: Option<NodeHandle>,
+}
+
+impl<T> AsyncQueue<T> {
+    pub fn new() -> AsyncQueue<T> {
+        AsyncQueue {
+            state: Mutex::new(State {
+                queue: Queue::new(),
+                waiters: DoublyLinkedList::new(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn enqueue(&self, val: T) {
+        let mut state = self.lock();
+        state.queue.enqueue(val);
+        let waiter = state.waiters.pop_front();
+        drop(state);
+        if let Some(waker) = waiter {
+            waker.wake();
+        }
+    }
+
+    pub fn try_dequeue(&self) -> Option<T> {
+        self.lock().queue.deque()
+    }
+
+    pub fn dequeue(&self) -> Dequeue<'_, T> {
+        Dequeue {
+            queue: self,
+            waiter: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a, T> Future for Dequeue<'a, T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.queue.lock();
+        if let Some(val) = state.queue.deque() {
+            if let Some(waiter) = self.waiter.take() {
+                state.waiters.remove(waiter);
+            }
+            return Poll::Ready(val);
+        }
+        let woken = self.waiter.is_some();
+        match self.waiter.and_then(|waiter| state.waiters.get_mut(waiter)) {
+            Some(waker) => waker.clone_from(cx.waker()),
+            // Woken, but another consumer got there first: wait again at the front so the
+            // wait order is kept.
+            None if woken => {
+                self.waiter = Some(state.waiters.push_front_handle(cx.waker().clone()));
+            }
+            None => self.waiter = Some(state.waiters.push_back_handle(cx.waker().clone())),
+        }
+        Poll::Pending
+    }
+}
+
+impl<'a, T> Drop for Dequeue<'a, T> {
+    fn drop(&mut self) {
+        let Some(waiter) = self.waiter else {
+            return;
+        };
+        let mut state = self.queue.lock();
+        if state.waiters.remove(waiter).is_some() {
+            return;
+        }
+        // This future was woken for an item it will now never take; hand the wakeup on.
+        if state.queue.is_empty() {
+            return;
+        }
+        let next = state.waiters.pop_front();
+        drop(state);
+        if let Some(waker) = next {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Default for AsyncQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for AsyncQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.lock();
+        f.debug_struct("AsyncQueue")
+            .field("len", &state.queue.len())
+            .field("waiters", &state.waiters.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::executor::{block_on, CountingWaker, Executor};
+    use std::{cell::RefCell, rc::Rc, sync::Arc, thread, time::Duration};
+
+    #[test]
+    fn it_works() {
+        let queue = AsyncQueue::new();
+        assert_eq!(queue.try_dequeue(), None);
+        queue.enqueue(1);
+        queue.enqueue(2);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(block_on(queue.dequeue()), 1);
+        assert_eq!(block_on(queue.dequeue()), 2);
+        assert!(queue.is_empty());
+
+        let queue = Arc::new(AsyncQueue::new());
+        let producer = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                queue.enqueue("late");
+            })
+        };
+        assert_eq!(block_on(queue.dequeue()), "late");
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn wakes_only_when_an_item_arrives() {
+        let queue = Rc::new(AsyncQueue::new());
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let mut executor = Executor::new();
+        for _ in 0..2 {
+            let (queue, received) = (queue.clone(), received.clone());
+            executor.spawn(async move {
+                let val = queue.dequeue().await;
+                received.borrow_mut().push(val);
+            });
+        }
+        assert_eq!(executor.run_until_stalled(), 2);
+        assert!(received.borrow().is_empty());
+        assert_eq!(executor.run_until_stalled(), 0);
+
+        queue.enqueue(10);
+        assert_eq!(executor.run_until_stalled(), 1);
+        assert_eq!(*received.borrow(), vec![10]);
+        queue.enqueue(20);
+        queue.enqueue(30);
+        assert_eq!(executor.run_until_stalled(), 1);
+        assert_eq!(*received.borrow(), vec![10, 20]);
+        assert_eq!(queue.try_dequeue(), Some(30));
+    }
+
+    #[test]
+    fn cancelled_dequeue_is_forgotten() {
+        let queue = AsyncQueue::new();
+        let (counter, waker) = CountingWaker::new();
+        let mut cx = Context::from_waker(&waker);
+        {
+            let mut fut = Box::pin(queue.dequeue());
+            assert!(fut.as_mut().poll(&mut cx).is_pending());
+            assert!(fut.as_mut().poll(&mut cx).is_pending());
+            assert_eq!(format!("{:?}", queue), "AsyncQueue { len: 0, waiters: 1 }");
+        }
+        assert_eq!(format!("{:?}", queue), "AsyncQueue { len: 0, waiters: 0 }");
+        queue.enqueue(1);
+        assert_eq!(counter.wakes(), 0);
+        assert_eq!(queue.try_dequeue(), Some(1));
+
+        let queue = Rc::new(AsyncQueue::new());
+        let mut executor = Executor::new();
+        let received = Rc::new(RefCell::new(None));
+        let cancelled = {
+            let queue = queue.clone();
+            executor.spawn(async move {
+                queue.dequeue().await;
+                unreachable!("cancelled task was polled to completion");
+            })
+        };
+        let waiting = {
+            let (queue, received) = (queue.clone(), received.clone());
+            executor.spawn(async move { *received.borrow_mut() = Some(queue.dequeue().await) })
+        };
+        executor.run_until_stalled();
+        executor.cancel(cancelled);
+        queue.enqueue(5);
+        executor.run_until_stalled();
+        assert!(executor.is_done(waiting));
+        assert_eq!(*received.borrow(), Some(5));
+    }
+
+    #[test]
+    fn dropped_wakeup_is_passed_on() {
+        let queue = AsyncQueue::new();
+        let (first_counter, first_waker) = CountingWaker::new();
+        let (second_counter, second_waker) = CountingWaker::new();
+        let mut first = Box::pin(queue.dequeue());
+        let mut second = Box::pin(queue.dequeue());
+        assert!(first
+            .as_mut()
+            .poll(&mut Context::from_waker(&first_waker))
+            .is_pending());
+        assert!(second
+            .as_mut()
+            .poll(&mut Context::from_waker(&second_waker))
+            .is_pending());
+
+        queue.enqueue(1);
+        assert_eq!((first_counter.wakes(), second_counter.wakes()), (1, 0));
+        drop(first);
+        assert_eq!(second_counter.wakes(), 1);
+        assert_eq!(
+            second
+                .as_mut()
+                .poll(&mut Context::from_waker(&second_waker)),
+            Poll::Ready(1)
+        );
+    }
+
+    #[test]
+    fn woken_consumer_that_lost_the_race_keeps_its_place() {
+        let queue = AsyncQueue::new();
+        let (first_counter, first_waker) = CountingWaker::new();
+        let (second_counter, second_waker) = CountingWaker::new();
+        let mut first = Box::pin(queue.dequeue());
+        let mut second = Box::pin(queue.dequeue());
+        let _ = first.as_mut().poll(&mut Context::from_waker(&first_waker));
+        let _ = second
+            .as_mut()
+            .poll(&mut Context::from_waker(&second_waker));
+
+        queue.enqueue(1);
+        assert_eq!(queue.try_dequeue(), Some(1));
+        assert!(first
+            .as_mut()
+            .poll(&mut Context::from_waker(&first_waker))
+            .is_pending());
+        queue.enqueue(2);
+        assert_eq!((first_counter.wakes(), second_counter.wakes()), (2, 0));
+    }
+
+    #[test]
+    fn many_threads() {
+        let queue = Arc::new(AsyncQueue::new());
+        let consumers: Vec<_> = (0..4)
+            .map(|_| {
+                let queue = queue.clone();
+                thread::spawn(move || (0..500).map(|_| block_on(queue.dequeue())).sum::<usize>())
+            })
+            .collect();
+        let producers: Vec<_> = (0..4)
+            .map(|p| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    for i in 0..500 {
+                        queue.enqueue(p * 500 + i);
+                    }
+                })
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        let total: usize = consumers.into_iter().map(|c| c.join().unwrap()).sum();
+        assert_eq!(total, (0..2000).sum());
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,527 @@
+use std::{
+    error::Error,
+    fmt,
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crate::queue::Queue;
+
+// The queue and the endpoint counts live under one lock. `not_empty` wakes receivers and
+// `not_full` wakes senders; both are also notified when the channel disconnects so nobody
+// stays blocked on a peer that is gone.
+pub struct Channel<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+struct State<T> {
+    queue: Queue<T>,
+    capacity: Option<usize>,
+    senders: usize,
+    receivers: usize,
+    closed: bool,
+}
+
+impl<T> State<T> {
+    fn is_full(&self) -> bool {
+        self.capacity
+            .is_some_and(|capacity| self.queue.len() >= capacity)
+    }
+
+    fn disconnected_for_senders(&self) -> bool {
+        self.closed || self.receivers == 0
+    }
+
+    fn disconnected_for_receivers(&self) -> bool {
+        self.closed || self.senders == 0
+    }
+}
+
+pub struct Sender<T> {
+    channel: Arc<Channel<T>>,
+}
+
+pub struct Receiver<T> {
+    channel: Arc<Channel<T>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    Full(T),
+    Disconnected(T),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    Empty,
+    Disconnected,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    Timeout,
+    Disconnected,
+}
+
+impl<T> Channel<T> {
+    pub fn bounded(capacity: usize) -> (Sender<T>, Receiver<T>) {
+        assert!(capacity > 0, "Channel capacity must be positive");
+        Channel::with_capacity(Some(capacity))
+    }
+
+    pub fn unbounded() -> (Sender<T>, Receiver<T>) {
+        Channel::with_capacity(None)
+    }
+
+    fn with_capacity(capacity: Option<usize>) -> (Sender<T>, Receiver<T>) {
+        let channel = Arc::new(Channel {
+            state: Mutex::new(State {
+                queue: Queue::new(),
+                capacity,
+                senders: 1,
+                receivers: 1,
+                closed: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        });
+        (
+            Sender {
+                channel: channel.clone(),
+            },
+            Receiver { channel },
+        )
+    }
+
+    // Queue operations cannot panic halfway, so a poisoned lock still guards a valid state.
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn wait<'a>(
+        &self,
+        condvar: &Condvar,
+        state: MutexGuard<'a, State<T>>,
+    ) -> MutexGuard<'a, State<T>> {
+        condvar.wait(state).unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn disconnect(&self, state: &mut State<T>) {
+        if state.disconnected_for_senders() {
+            self.not_full.notify_all();
+        }
+        if state.disconnected_for_receivers() {
+            self.not_empty.notify_all();
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.lock().queue.len()
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        self.lock().capacity
+    }
+
+    fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        self.disconnect(&mut state);
+    }
+
+    fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+}
+
+impl<T> Sender<T> {
+    pub fn send(&self, val: T) -> Result<(), SendError<T>> {
+        let channel = &*self.channel;
+        let mut state = channel.lock();
+        loop {
+            if state.disconnected_for_senders() {
+                return Err(SendError(val));
+            }
+            if !state.is_full() {
+                break;
+            }
+            state = channel.wait(&channel.not_full, state);
+        }
+        state.queue.enqueue(val);
+        channel.not_empty.notify_one();
+        Ok(())
+    }
+
+    pub fn try_send(&self, val: T) -> Result<(), TrySendError<T>> {
+        let mut state = self.channel.lock();
+        if state.disconnected_for_senders() {
+            return Err(TrySendError::Disconnected(val));
+        }
+        if state.is_full() {
+            return Err(TrySendError::Full(val));
+        }
+        state.queue.enqueue(val);
+        self.channel.not_empty.notify_one();
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.channel.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.channel.capacity()
+    }
+
+    // Disconnects every endpoint; receivers can still drain what was already sent.
+    pub fn close(&self) {
+        self.channel.close();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.channel.is_closed()
+    }
+}
+
+impl<T> Receiver<T> {
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let channel = &*self.channel;
+        let mut state = channel.lock();
+        loop {
+            if let Some(val) = state.queue.deque() {
+                channel.not_full.notify_one();
+                return Ok(val);
+            }
+            if state.disconnected_for_receivers() {
+                return Err(RecvError);
+            }
+            state = channel.wait(&channel.not_empty, state);
+        }
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.channel.lock();
+        match state.queue.deque() {
+            Some(val) => {
+                self.channel.not_full.notify_one();
+                Ok(val)
+            }
+            None if state.disconnected_for_receivers() => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    // A timeout too large to express as an `Instant` waits forever, like `recv`.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let Some(deadline) = Instant::now().checked_add(timeout) else {
+            return self
+                .recv()
+                .map_err(|RecvError| RecvTimeoutError::Disconnected);
+        };
+        let channel = &*self.channel;
+        let mut state = channel.lock();
+        loop {
+            if let Some(val) = state.queue.deque() {
+                channel.not_full.notify_one();
+                return Ok(val);
+            }
+            if state.disconnected_for_receivers() {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(RecvTimeoutError::Timeout);
+            }
+            state = channel
+                .not_empty
+                .wait_timeout(state, deadline - now)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+
+    // Blocks for each item and ends once the channel is disconnected and drained.
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        std::iter::from_fn(move || self.recv().ok())
+    }
+
+    pub fn len(&self) -> usize {
+        self.channel.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        self.channel.capacity()
+    }
+
+    pub fn close(&self) {
+        self.channel.close();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.channel.is_closed()
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.channel.lock().senders += 1;
+        Sender {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.channel.lock().receivers += 1;
+        Receiver {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.channel.lock();
+        state.senders -= 1;
+        self.channel.disconnect(&mut state);
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.channel.lock();
+        state.receivers -= 1;
+        self.channel.disconnect(&mut state);
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("sending on a disconnected channel")
+    }
+}
+
+impl<T: fmt::Debug> Error for SendError<T> {}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.write_str("sending on a full channel"),
+            TrySendError::Disconnected(_) => f.write_str("sending on a disconnected channel"),
+        }
+    }
+}
+
+impl<T: fmt::Debug> Error for TrySendError<T> {}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("receiving on a disconnected channel")
+    }
+}
+
+impl Error for RecvError {}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => f.write_str("receiving on an empty channel"),
+            TryRecvError::Disconnected => f.write_str("receiving on a disconnected channel"),
+        }
+    }
+}
+
+impl Error for TryRecvError {}
+
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => f.write_str("timed out waiting on a channel"),
+            RecvTimeoutError::Disconnected => f.write_str("receiving on a disconnected channel"),
+        }
+    }
+}
+
+impl Error for RecvTimeoutError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn it_works() {
+        let (tx, rx) = Channel::unbounded();
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        assert_eq!(rx.len(), 2);
+        assert_eq!(rx.recv(), Ok(1));
+        assert_eq!(rx.try_recv(), Ok(2));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(tx.capacity(), None);
+
+        let handle = thread::spawn(move || {
+            for i in 0..100 {
+                tx.send(i).unwrap();
+            }
+        });
+        assert_eq!(rx.iter().sum::<i32>(), 4950);
+        handle.join().unwrap();
+        assert_eq!(rx.recv(), Err(RecvError));
+    }
+
+    #[test]
+    fn backpressure() {
+        let (tx, rx) = Channel::bounded(2);
+        assert_eq!(tx.try_send(1), Ok(()));
+        assert_eq!(tx.try_send(2), Ok(()));
+        assert_eq!(tx.try_send(3), Err(TrySendError::Full(3)));
+
+        let sender = thread::spawn(move || {
+            tx.send(3).unwrap();
+            tx.send(4).unwrap();
+        });
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(rx.len(), 2);
+        assert_eq!(rx.iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        sender.join().unwrap();
+    }
+
+    #[test]
+    fn disconnects() {
+        let (tx, rx) = Channel::<i32>::bounded(1);
+        let rx2 = rx.clone();
+        drop(rx);
+        tx.send(1).unwrap();
+        drop(rx2);
+        assert_eq!(tx.send(2), Err(SendError(2)));
+        assert_eq!(tx.try_send(3), Err(TrySendError::Disconnected(3)));
+
+        // A sender blocked on a full channel is released when the last receiver goes away.
+        let (tx, rx) = Channel::bounded(1);
+        tx.send(1).unwrap();
+        let blocked = thread::spawn(move || tx.send(2));
+        thread::sleep(Duration::from_millis(20));
+        drop(rx);
+        assert_eq!(blocked.join().unwrap(), Err(SendError(2)));
+
+        let (tx, rx) = Channel::unbounded();
+        let tx2 = tx.clone();
+        tx.send(1).unwrap();
+        drop(tx);
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        drop(tx2);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn close_drains_then_disconnects() {
+        let (tx, rx) = Channel::unbounded();
+        tx.send("a").unwrap();
+        tx.send("b").unwrap();
+        let waiting = {
+            let rx = rx.clone();
+            thread::spawn(move || rx.iter().count())
+        };
+        thread::sleep(Duration::from_millis(20));
+        rx.close();
+        assert!(tx.is_closed());
+        assert_eq!(tx.send("c"), Err(SendError("c")));
+        assert_eq!(waiting.join().unwrap() + rx.iter().count(), 2);
+        assert_eq!(rx.recv(), Err(RecvError));
+    }
+
+    #[test]
+    fn recv_timeout() {
+        let (tx, rx) = Channel::unbounded();
+        let start = Instant::now();
+        assert_eq!(
+            rx.recv_timeout(Duration::from_millis(30)),
+            Err(RecvTimeoutError::Timeout)
+        );
+        assert!(start.elapsed() >= Duration::from_millis(30));
+
+        let sender = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            tx.send(7).unwrap();
+        });
+        assert_eq!(rx.recv_timeout(Duration::from_secs(10)), Ok(7));
+        sender.join().unwrap();
+        assert_eq!(
+            rx.recv_timeout(Duration::MAX),
+            Err(RecvTimeoutError::Disconnected)
+        );
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(10)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+        assert_eq!(
+            RecvTimeoutError::Timeout.to_string(),
+            "timed out waiting on a channel"
+        );
+    }
+
+    #[test]
+    fn many_producers_and_consumers() {
+        const PRODUCERS: usize = 4;
+        const PER_PRODUCER: usize = 5000;
+        let (tx, rx) = Channel::bounded(16);
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|p| {
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        tx.send(p * PER_PRODUCER + i).unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+        let consumers: Vec<_> = (0..3)
+            .map(|_| {
+                let rx = rx.clone();
+                thread::spawn(move || rx.iter().collect::<Vec<_>>())
+            })
+            .collect();
+        drop(rx);
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        let mut received: Vec<usize> = consumers
+            .into_iter()
+            .flat_map(|consumer| consumer.join().unwrap())
+            .collect();
+        received.sort();
+        assert_eq!(received, (0..PRODUCERS * PER_PRODUCER).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    future::Future,
+    pin::{pin, Pin},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+};
+
+// Just enough of an async runtime to drive the futures in this module from tests.
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+pub(crate) fn block_on<F: Future>(fut: F) -> F::Output {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = pin!(fut);
+    loop {
+        if let Poll::Ready(val) = fut.as_mut().poll(&mut cx) {
+            return val;
+        }
+        thread::park();
+    }
+}
+
+pub(crate) struct CountingWaker(AtomicUsize);
+
+impl CountingWaker {
+    pub(crate) fn new() -> (Arc<CountingWaker>, Waker) {
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        (counter.clone(), Waker::from(counter))
+    }
+
+    pub(crate) fn wakes(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+struct TaskWaker {
+    id: usize,
+    ready: Arc<Mutex<Vec<usize>>>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.ready.lock().unwrap().push(self.id);
+    }
+}
+
+// A single-threaded executor that only polls tasks whose waker fired, so a test can check
+// that a task was (or was not) woken by looking at what ran.
+pub(crate) struct Executor {
+    tasks: Vec<Option<Pin<Box<dyn Future<Output = ()>>>>>,
+    ready: Arc<Mutex<Vec<usize>>>,
+}
+
+impl Executor {
+    pub(crate) fn new() -> Executor {
+        Executor {
+            tasks: Vec::new(),
+            ready: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub(crate) fn spawn(&mut self, fut: impl Future<Output = ()> + 'static) -> usize {
+        let id = self.tasks.len();
+        self.tasks.push(Some(Box::pin(fut)));
+        self.ready.lock().unwrap().push(id);
+        id
+    }
+
+    pub(crate) fn cancel(&mut self, id: usize) {
+        self.tasks[id] = None;
+    }
+
+    pub(crate) fn is_done(&self, id: usize) -> bool {
+        self.tasks[id].is_none()
+    }
+
+    // Polls woken tasks until none are left, returning how many polls that took.
+    pub(crate) fn run_until_stalled(&mut self) -> usize {
+        let mut polls = 0;
+        loop {
+            let ready = std::mem::take(&mut *self.ready.lock().unwrap());
+            if ready.is_empty() {
+                return polls;
+            }
+            for id in ready {
+                let Some(task) = self.tasks[id].as_mut() else {
+                    continue;
+                };
+                let waker = Waker::from(Arc::new(TaskWaker {
+                    id,
+                    ready: self.ready.clone(),
+                }));
+                polls += 1;
+                if task
+                    .as_mut()
+                    .poll(&mut Context::from_waker(&waker))
+                    .is_ready()
+                {
+                    self.tasks[id] = None;
+                }
+            }
+        }
+    }
+}
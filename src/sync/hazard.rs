@@ -0,0 +1,187 @@
+use std::{
+    cell::UnsafeCell,
+    ptr,
+    sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering},
+};
+
+pub(crate) const HAZARDS_PER_RECORD: usize = 2;
+
+const MIN_RETIRED_BEFORE_SCAN: usize = 64;
+
+// Hazard pointers for lock-free structures whose nodes are `Box<N>` allocations. A thread
+// publishes the nodes it is about to dereference in a record's hazard slots; unlinked nodes
+// are retired into the record and only freed once no slot in the domain points at them.
+//
+// Records are claimed for the duration of one operation rather than pinned to a thread, and
+// are never freed before the domain itself, so the record list only ever grows to the peak
+// number of concurrent operations.
+pub(crate) struct HazardDomain<N> {
+    records: AtomicPtr<HazardRecord<N>>,
+    record_count: AtomicUsize,
+}
+
+struct HazardRecord<N> {
+    active: AtomicBool,
+    hazards: [AtomicPtr<N>; HAZARDS_PER_RECORD],
+    // Only touched by whoever holds the record (`active`).
+    retired: UnsafeCell<Vec<*mut N>>,
+    next: *mut HazardRecord<N>,
+}
+
+pub(crate) struct HazardGuard<'a, N> {
+    domain: &'a HazardDomain<N>,
+    record: &'a HazardRecord<N>,
+}
+
+impl<N> HazardDomain<N> {
+    pub(crate) fn new() -> HazardDomain<N> {
+        HazardDomain {
+            records: AtomicPtr::new(ptr::null_mut()),
+            record_count: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn acquire(&self) -> HazardGuard<'_, N> {
+        let mut curr = self.records.load(Ordering::Acquire);
+        while let Some(record) = unsafe { curr.as_ref() } {
+            if !record.active.load(Ordering::Relaxed)
+                && record
+                    .active
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                return HazardGuard {
+                    domain: self,
+                    record,
+                };
+            }
+            curr = record.next;
+        }
+
+        let record = Box::into_raw(Box::new(HazardRecord {
+            active: AtomicBool::new(true),
+            hazards: Default::default(),
+            retired: UnsafeCell::new(Vec::new()),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.records.load(Ordering::Relaxed);
+        loop {
+            unsafe { (*record).next = head };
+            match self.records.compare_exchange_weak(
+                head,
+                record,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(actual) => head = actual,
+            }
+        }
+        self.record_count.fetch_add(1, Ordering::Relaxed);
+        HazardGuard {
+            domain: self,
+            record: unsafe { &*record },
+        }
+    }
+
+    fn records(&self) -> impl Iterator<Item = &HazardRecord<N>> {
+        let mut curr = self.records.load(Ordering::Acquire);
+        std::iter::from_fn(move || {
+            let record = unsafe { curr.as_ref()? };
+            curr = record.next;
+            Some(record)
+        })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn pending_retired(&mut self) -> usize {
+        self.records()
+            .map(|record| unsafe { (*record.retired.get()).len() })
+            .sum()
+    }
+}
+
+impl<'a, N> HazardGuard<'a, N> {
+    // Loads `src` and publishes it in hazard slot `slot`, retrying until the published value
+    // is still current, at which point it cannot be freed until the slot is cleared. That
+    // relies on the store-then-load here and the unlink-then-`scan` on the retiring side all
+    // being SeqCst, so callers must unlink nodes from `src` with a SeqCst write.
+    pub(crate) fn protect(&self, slot: usize, src: &AtomicPtr<N>) -> *mut N {
+        let mut ptr = src.load(Ordering::Acquire);
+        loop {
+            self.record.hazards[slot].store(ptr, Ordering::SeqCst);
+            let current = src.load(Ordering::SeqCst);
+            if current == ptr {
+                return ptr;
+            }
+            ptr = current;
+        }
+    }
+
+    // Publishes `ptr` without validating it; the caller re-checks that it is still reachable.
+    pub(crate) fn set(&self, slot: usize, ptr: *mut N) {
+        self.record.hazards[slot].store(ptr, Ordering::SeqCst);
+    }
+
+    pub(crate) fn clear(&self) {
+        for hazard in &self.record.hazards {
+            hazard.store(ptr::null_mut(), Ordering::Release);
+        }
+    }
+
+    // `node` must already be unreachable for threads that have not protected it, and must
+    // have been allocated with `Box`.
+    pub(crate) unsafe fn retire(&self, node: *mut N) {
+        let retired = &mut *self.record.retired.get();
+        retired.push(node);
+        let threshold = MIN_RETIRED_BEFORE_SCAN
+            .max(2 * HAZARDS_PER_RECORD * self.domain.record_count.load(Ordering::Relaxed));
+        if retired.len() >= threshold {
+            self.scan();
+        }
+    }
+
+    fn scan(&self) {
+        let mut protected: Vec<*mut N> = self
+            .domain
+            .records()
+            .flat_map(|record| &record.hazards)
+            .map(|hazard| hazard.load(Ordering::SeqCst))
+            .filter(|ptr| !ptr.is_null())
+            .collect();
+        protected.sort_unstable();
+        let retired = unsafe { &mut *self.record.retired.get() };
+        retired.retain(|&node| {
+            if protected.binary_search(&node).is_ok() {
+                return true;
+            }
+            drop(unsafe { Box::from_raw(node) });
+            false
+        });
+    }
+}
+
+impl<'a, N> Drop for HazardGuard<'a, N> {
+    fn drop(&mut self) {
+        self.clear();
+        self.record.active.store(false, Ordering::Release);
+    }
+}
+
+impl<N> Drop for HazardDomain<N> {
+    fn drop(&mut self) {
+        let mut curr = *self.records.get_mut();
+        while !curr.is_null() {
+            let record = unsafe { Box::from_raw(curr) };
+            for &node in unsafe { &*record.retired.get() } {
+                drop(unsafe { Box::from_raw(node) });
+            }
+            curr = record.next;
+        }
+    }
+}
+
+// Retired nodes are only ever freed, never read, so the domain can move between threads
+// whenever the nodes themselves could.
+unsafe impl<N: Send> Send for HazardDomain<N> {}
+unsafe impl<N: Send> Sync for HazardDomain<N> {}
@@ -0,0 +1,292 @@
+use std::{
+    fmt,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use super::hazard::HazardDomain;
+
+struct Node<T> {
+    value: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>,
+}
+
+// A Michael–Scott queue. `head` always points at a dummy node whose value has already been
+// taken (or never existed); the live values are in the nodes after it. Dequeuing swings
+// `head` forward and retires the old dummy through the hazard domain, so a thread that is
+// still looking at it never reads freed memory.
+pub struct LockFreeQueue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    hazards: HazardDomain<Node<T>>,
+    _boo: PhantomData<T>,
+}
+
+impl<T> Node<T> {
+    fn alloc(value: MaybeUninit<T>) -> *mut Node<T> {
+        Box::into_raw(Box::new(Node {
+            value,
+            next: AtomicPtr::new(ptr::null_mut()),
+        }))
+    }
+}
+
+impl<T> LockFreeQueue<T> {
+    pub fn new() -> LockFreeQueue<T> {
+        let dummy = Node::alloc(MaybeUninit::uninit());
+        LockFreeQueue {
+            head: AtomicPtr::new(dummy),
+            tail: AtomicPtr::new(dummy),
+            hazards: HazardDomain::new(),
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn enqueue(&self, val: T) {
+        let new = Node::alloc(MaybeUninit::new(val));
+        let guard = self.hazards.acquire();
+        loop {
+            let tail = guard.protect(0, &self.tail);
+            let next = unsafe { (*tail).next.load(Ordering::Acquire) };
+            if tail != self.tail.load(Ordering::Acquire) {
+                continue;
+            }
+            if !next.is_null() {
+                // Another enqueue linked its node but has not moved `tail` yet; help it along.
+                let _ = self
+                    .tail
+                    .compare_exchange(tail, next, Ordering::SeqCst, Ordering::Relaxed);
+                continue;
+            }
+            let linked = unsafe {
+                (*tail).next.compare_exchange(
+                    ptr::null_mut(),
+                    new,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                )
+            };
+            if linked.is_ok() {
+                let _ = self
+                    .tail
+                    .compare_exchange(tail, new, Ordering::SeqCst, Ordering::Relaxed);
+                return;
+            }
+        }
+    }
+
+    pub fn deque(&self) -> Option<T> {
+        let guard = self.hazards.acquire();
+        loop {
+            let head = guard.protect(0, &self.head);
+            let tail = self.tail.load(Ordering::Acquire);
+            let next = unsafe { (*head).next.load(Ordering::Acquire) };
+            guard.set(1, next);
+            // While `head` is unchanged, `next` is still linked and so cannot have been retired.
+            if head != self.head.load(Ordering::SeqCst) {
+                continue;
+            }
+            if next.is_null() {
+                return None;
+            }
+            // Like every swing of `head` and `tail`, these are SeqCst so that they are ordered
+            // against the hazard stores and loads; see `HazardGuard::protect`.
+            if head == tail {
+                let _ = self
+                    .tail
+                    .compare_exchange(tail, next, Ordering::SeqCst, Ordering::Relaxed);
+                continue;
+            }
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok()
+            {
+                // `next` is the new dummy; only the thread that won the swing reads its value.
+                let val = unsafe { (*next).value.assume_init_read() };
+                guard.clear();
+                unsafe { guard.retire(head) };
+                return Some(val);
+            }
+        }
+    }
+
+    // A snapshot: other threads may change the answer before the caller acts on it.
+    pub fn is_empty(&self) -> bool {
+        let guard = self.hazards.acquire();
+        let head = guard.protect(0, &self.head);
+        unsafe { (*head).next.load(Ordering::Acquire).is_null() }
+    }
+}
+
+impl<T> Drop for LockFreeQueue<T> {
+    fn drop(&mut self) {
+        let dummy = unsafe { Box::from_raw(*self.head.get_mut()) };
+        let mut curr = dummy.next.load(Ordering::Relaxed);
+        while !curr.is_null() {
+            let mut node = unsafe { Box::from_raw(curr) };
+            unsafe { node.value.assume_init_drop() };
+            curr = node.next.load(Ordering::Relaxed);
+        }
+    }
+}
+
+impl<T> Default for LockFreeQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for LockFreeQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LockFreeQueue").finish_non_exhaustive()
+    }
+}
+
+// Values only ever move in and out whole, so sharing the queue needs nothing beyond `Send`.
+unsafe impl<T: Send> Send for LockFreeQueue<T> {}
+unsafe impl<T: Send> Sync for LockFreeQueue<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::DropCounter;
+    use std::{
+        cell::Cell,
+        sync::{
+            atomic::{AtomicBool, AtomicUsize},
+            Arc,
+        },
+        thread,
+    };
+
+    #[test]
+    fn it_works() {
+        let queue = LockFreeQueue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.deque(), None);
+        queue.enqueue(5);
+        queue.enqueue(7);
+        queue.enqueue(9);
+        assert!(!queue.is_empty());
+        assert_eq!(queue.deque(), Some(5));
+        queue.enqueue(11);
+        assert_eq!(queue.deque(), Some(7));
+        assert_eq!(queue.deque(), Some(9));
+        assert_eq!(queue.deque(), Some(11));
+        assert_eq!(queue.deque(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn reclaims_nodes() {
+        let mut queue = LockFreeQueue::new();
+        for round in 0..100 {
+            for i in 0..100 {
+                queue.enqueue(round * 100 + i);
+            }
+            for i in 0..100 {
+                assert_eq!(queue.deque(), Some(round * 100 + i));
+            }
+        }
+        assert!(queue.hazards.pending_retired() < 64);
+
+        let drops = Cell::new(0);
+        let queue = LockFreeQueue::new();
+        for _ in 0..10 {
+            queue.enqueue(DropCounter(&drops));
+        }
+        drop(queue.deque());
+        assert_eq!(drops.get(), 1);
+        drop(queue);
+        assert_eq!(drops.get(), 10);
+    }
+
+    #[test]
+    fn stress_many_producers_and_consumers() {
+        const PRODUCERS: usize = 4;
+        const CONSUMERS: usize = 4;
+        const PER_PRODUCER: usize = 20_000;
+        let queue = Arc::new(LockFreeQueue::<usize>::new());
+        let seen: Arc<Vec<AtomicUsize>> = Arc::new(
+            (0..PRODUCERS * PER_PRODUCER)
+                .map(|_| AtomicUsize::new(0))
+                .collect(),
+        );
+        let done = Arc::new(AtomicBool::new(false));
+
+        let consumers: Vec<_> = (0..CONSUMERS)
+            .map(|_| {
+                let (queue, seen, done) = (queue.clone(), seen.clone(), done.clone());
+                thread::spawn(move || {
+                    // Values from one producer must come out in the order it sent them.
+                    let mut last = [None; PRODUCERS];
+                    loop {
+                        // Every enqueue happens before `done` is set, so a drain that starts
+                        // after seeing it cannot miss a value.
+                        let finished = done.load(Ordering::Acquire);
+                        while let Some(val) = queue.deque() {
+                            let producer = val / PER_PRODUCER;
+                            assert!(last[producer] < Some(val));
+                            last[producer] = Some(val);
+                            seen[val].fetch_add(1, Ordering::Relaxed);
+                        }
+                        if finished {
+                            break;
+                        }
+                        thread::yield_now();
+                    }
+                })
+            })
+            .collect();
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|p| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        queue.enqueue(p * PER_PRODUCER + i);
+                    }
+                })
+            })
+            .collect();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        done.store(true, Ordering::Release);
+        for consumer in consumers {
+            consumer.join().unwrap();
+        }
+        assert!(queue.is_empty());
+        assert!(seen.iter().all(|count| count.load(Ordering::Relaxed) == 1));
+    }
+
+    #[test]
+    fn stress_with_heap_values() {
+        let queue = Arc::new(LockFreeQueue::new());
+        let total = Arc::new(AtomicUsize::new(0));
+        let workers: Vec<_> = (0..8)
+            .map(|w| {
+                let (queue, total) = (queue.clone(), total.clone());
+                thread::spawn(move || {
+                    for i in 0..5000 {
+                        queue.enqueue(vec![w; i % 7]);
+                        if let Some(val) = queue.deque() {
+                            total.fetch_add(val.len(), Ordering::Relaxed);
+                        }
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        while let Some(val) = queue.deque() {
+            total.fetch_add(val.len(), Ordering::Relaxed);
+        }
+        let expected: usize = (0..5000).map(|i| i % 7).sum::<usize>() * 8;
+        assert_eq!(total.load(Ordering::Relaxed), expected);
+    }
+}
@@ -0,0 +1,23 @@
+use std::cell::Cell;
+
+// Element types for checking that containers drop everything they own exactly once.
+#[derive(Debug)]
+pub(crate) struct DropCounter<'a>(pub(crate) &'a Cell<usize>);
+
+impl<'a> Drop for DropCounter<'a> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+// Counts its drop like `DropCounter`, then panics if the flag is set.
+pub(crate) struct PanicOnDrop<'a>(pub(crate) &'a Cell<usize>, pub(crate) bool);
+
+impl<'a> Drop for PanicOnDrop<'a> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+        if self.1 {
+            panic!("boom");
+        }
+    }
+}
@@ -0,0 +1,480 @@
+use std::{
+    fmt, mem,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use crate::linked_list::{DoublyLinkedList, NodeHandle};
+
+const SLOT_BITS: u32 = 6;
+const SLOTS: usize = 1 << SLOT_BITS;
+const LEVELS: usize = 6;
+// Timers at least this far past `elapsed` cannot be given their own top-level slot.
+const PARK_TICKS: u64 = (SLOTS as u64 - 1) << (SLOT_BITS * (LEVELS as u32 - 1));
+
+// Time is counted in ticks; the wheel never looks at the clock except through `Clock`, so
+// tests can drive it by hand.
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+// Milliseconds since the clock was created.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Arc<AtomicU64>,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+}
+
+impl ManualClock {
+    pub fn new(now: u64) -> ManualClock {
+        ManualClock {
+            now: Arc::new(AtomicU64::new(now)),
+        }
+    }
+
+    pub fn set(&self, now: u64) {
+        self.now.store(now, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, ticks: u64) {
+        self.now.fetch_add(ticks, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
+// A hierarchical timer wheel: level `l` has 64 slots of 64^l ticks each, and a timer sits at
+// the lowest level whose slot tells its deadline apart from `elapsed`. When `elapsed` reaches
+// a slot on a higher level, its timers cascade down (or become ready), so a timer within
+// reach of the top level is moved at most once per level. Timers beyond it wait in the top
+// slot that comes up last and are re-filed every time it does, once per 2^36-tick rotation,
+// until their deadline is in reach.
+pub struct TimerWheel<T, C = SystemClock> {
+    clock: C,
+    // Every timer with a deadline at or before `elapsed` is in `ready`.
+    elapsed: u64,
+    levels: [[DoublyLinkedList<Entry<T>>; SLOTS]; LEVELS],
+    occupied: [u64; LEVELS],
+    ready: DoublyLinkedList<Entry<T>>,
+    timers: Vec<Timer>,
+    free: Vec<usize>,
+    len: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerHandle {
+    index: usize,
+    generation: u64,
+}
+
+struct Entry<T> {
+    index: usize,
+    deadline: u64,
+    item: T,
+}
+
+struct Timer {
+    generation: u64,
+    location: Option<(Location, NodeHandle)>,
+}
+
+#[derive(Clone, Copy)]
+enum Location {
+    Ready,
+    Slot(usize, usize),
+}
+
+impl<T> TimerWheel<T> {
+    pub fn new() -> TimerWheel<T> {
+        TimerWheel::with_clock(SystemClock::new())
+    }
+}
+
+impl<T, C: Clock> TimerWheel<T, C> {
+    pub fn with_clock(clock: C) -> TimerWheel<T, C> {
+        TimerWheel {
+            elapsed: clock.now(),
+            clock,
+            levels: std::array::from_fn(|_| std::array::from_fn(|_| DoublyLinkedList::new())),
+            occupied: [0; LEVELS],
+            ready: DoublyLinkedList::new(),
+            timers: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // A deadline that has already passed fires on the next poll.
+    pub fn insert(&mut self, item: T, deadline: u64) -> TimerHandle {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.timers.push(Timer {
+                    generation: 0,
+                    location: None,
+                });
+                self.timers.len() - 1
+            }
+        };
+        self.len += 1;
+        self.place(Entry {
+            index,
+            deadline,
+            item,
+        });
+        TimerHandle {
+            index,
+            generation: self.timers[index].generation,
+        }
+    }
+
+    pub fn insert_after(&mut self, item: T, delay: u64) -> TimerHandle {
+        let deadline = self.clock.now().saturating_add(delay);
+        self.insert(item, deadline)
+    }
+
+    pub fn deadline(&self, handle: TimerHandle) -> Option<u64> {
+        let (location, node) = self.locate(handle)?;
+        self.list(location).get(node).map(|entry| entry.deadline)
+    }
+
+    pub fn cancel(&mut self, handle: TimerHandle) -> Option<T> {
+        let entry = self.unlink(handle)?;
+        self.release(entry.index);
+        Some(entry.item)
+    }
+
+    pub fn reset(&mut self, handle: TimerHandle, deadline: u64) -> bool {
+        let Some(mut entry) = self.unlink(handle) else {
+            return false;
+        };
+        entry.deadline = deadline;
+        self.place(entry);
+        true
+    }
+
+    // Hands back expired timers one at a time, earliest deadline first. Time never runs
+    // backwards: a `now` before an earlier poll only drains what is already ready.
+    pub fn poll_expired(&mut self, now: u64) -> Option<T> {
+        loop {
+            if let Some(entry) = self.ready.pop_front() {
+                self.release(entry.index);
+                return Some(entry.item);
+            }
+            match self.next_expiration() {
+                Some((level, slot, when)) if when <= now => self.cascade(level, slot, when),
+                _ => {
+                    self.elapsed = self.elapsed.max(now);
+                    return None;
+                }
+            }
+        }
+    }
+
+    pub fn poll(&mut self) -> Option<T> {
+        let now = self.clock.now();
+        self.poll_expired(now)
+    }
+
+    fn locate(&self, handle: TimerHandle) -> Option<(Location, NodeHandle)> {
+        let timer = self.timers.get(handle.index)?;
+        if timer.generation != handle.generation {
+            return None;
+        }
+        timer.location
+    }
+
+    fn list(&self, location: Location) -> &DoublyLinkedList<Entry<T>> {
+        match location {
+            Location::Ready => &self.ready,
+            Location::Slot(level, slot) => &self.levels[level][slot],
+        }
+    }
+
+    fn unlink(&mut self, handle: TimerHandle) -> Option<Entry<T>> {
+        let (location, node) = self.locate(handle)?;
+        let entry = match location {
+            Location::Ready => self.ready.remove(node)?,
+            Location::Slot(level, slot) => {
+                let list = &mut self.levels[level][slot];
+                let entry = list.remove(node)?;
+                if list.is_empty() {
+                    self.occupied[level] &= !(1 << slot);
+                }
+                entry
+            }
+        };
+        self.timers[handle.index].location = None;
+        Some(entry)
+    }
+
+    fn release(&mut self, index: usize) {
+        let timer = &mut self.timers[index];
+        timer.location = None;
+        timer.generation += 1;
+        self.free.push(index);
+        self.len -= 1;
+    }
+
+    fn place(&mut self, entry: Entry<T>) {
+        let index = entry.index;
+        let top = LEVELS - 1;
+        let location = if entry.deadline <= self.elapsed {
+            Location::Ready
+        } else if entry.deadline - self.elapsed >= PARK_TICKS {
+            // Too far out for the top level to place exactly: wait in the top slot that
+            // comes up last, which still starts before the deadline.
+            Location::Slot(top, (slot_index(self.elapsed, top) + SLOTS - 1) % SLOTS)
+        } else {
+            // The highest bit where the deadline differs from `elapsed` picks the level.
+            let level =
+                ((63 - (self.elapsed ^ entry.deadline).leading_zeros()) / SLOT_BITS) as usize;
+            let level = level.min(top);
+            Location::Slot(level, slot_index(entry.deadline, level))
+        };
+        let node = match location {
+            // Cascades deliver deadlines in order, but a timer inserted already late can
+            // belong ahead of some of `ready`. Ties stay in insertion order.
+            Location::Ready => {
+                let mut cursor = self.ready.cursor_back_mut();
+                while cursor
+                    .current()
+                    .is_some_and(|ready| ready.deadline > entry.deadline)
+                {
+                    cursor.move_prev();
+                }
+                cursor.insert_after_handle(entry)
+            }
+            Location::Slot(level, slot) => {
+                self.occupied[level] |= 1 << slot;
+                self.levels[level][slot].push_back_handle(entry)
+            }
+        };
+        self.timers[index].location = Some((location, node));
+    }
+
+    // The first occupied slot from `elapsed` on, and the tick at which it starts. Slots on
+    // lower levels always come before those on higher ones.
+    fn next_expiration(&self) -> Option<(usize, usize, u64)> {
+        (0..LEVELS).find_map(|level| {
+            let occupied = self.occupied[level];
+            if occupied == 0 {
+                return None;
+            }
+            let slot_ticks = 1u64 << (SLOT_BITS * level as u32);
+            let level_ticks = slot_ticks << SLOT_BITS;
+            let pos = slot_index(self.elapsed, level);
+            let slot = (pos + occupied.rotate_right(pos as u32).trailing_zeros() as usize) % SLOTS;
+            let mut when = (self.elapsed & !(level_ticks - 1)) + slot as u64 * slot_ticks;
+            // Only the top level wraps around, for timers due in its next rotation.
+            if slot < pos {
+                when += level_ticks;
+            }
+            Some((level, slot, when))
+        })
+    }
+
+    fn cascade(&mut self, level: usize, slot: usize, when: u64) {
+        self.elapsed = when;
+        self.occupied[level] &= !(1 << slot);
+        let mut list = mem::take(&mut self.levels[level][slot]);
+        while let Some(entry) = list.pop_front() {
+            self.place(entry);
+        }
+    }
+}
+
+fn slot_index(tick: u64, level: usize) -> usize {
+    ((tick >> (SLOT_BITS * level as u32)) as usize) & (SLOTS - 1)
+}
+
+impl<T> Default for TimerWheel<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, C> fmt::Debug for TimerWheel<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimerWheel")
+            .field("len", &self.len)
+            .field("elapsed", &self.elapsed)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn drain<T, C: Clock>(wheel: &mut TimerWheel<T, C>, now: u64) -> Vec<T> {
+        std::iter::from_fn(|| wheel.poll_expired(now)).collect()
+    }
+
+    #[test]
+    fn it_works() {
+        let clock = ManualClock::new(100);
+        let mut wheel = TimerWheel::with_clock(clock.clone());
+        assert_eq!(wheel.poll(), None);
+        wheel.insert_after("b", 20);
+        wheel.insert_after("a", 10);
+        wheel.insert("c", 5000);
+        wheel.insert("late", 50);
+        assert_eq!(wheel.len(), 4);
+
+        assert_eq!(wheel.poll(), Some("late"));
+        assert_eq!(wheel.poll(), None);
+        clock.advance(9);
+        assert_eq!(wheel.poll(), None);
+        clock.advance(1);
+        assert_eq!(wheel.poll(), Some("a"));
+        clock.set(4999);
+        assert_eq!(wheel.poll(), Some("b"));
+        assert_eq!(wheel.poll(), None);
+        clock.set(6000);
+        assert_eq!(wheel.poll(), Some("c"));
+        assert_eq!(wheel.poll(), None);
+        assert!(wheel.is_empty());
+        assert_eq!(
+            format!("{:?}", wheel),
+            "TimerWheel { len: 0, elapsed: 6000 }"
+        );
+    }
+
+    #[test]
+    fn expires_in_deadline_order() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut wheel = TimerWheel::with_clock(ManualClock::new(0));
+        let mut deadlines: Vec<u64> = (0..2000)
+            .map(|_| {
+                let bits = rng.gen_range(1..40);
+                rng.gen_range(0..1u64 << bits)
+            })
+            .collect();
+        for &deadline in &deadlines {
+            wheel.insert(deadline, deadline);
+        }
+        deadlines.sort_unstable();
+
+        let mut now = 0;
+        let mut fired = Vec::new();
+        while fired.len() < deadlines.len() {
+            let bits = rng.gen_range(1..36);
+            now += rng.gen_range(1..1u64 << bits);
+            for deadline in drain(&mut wheel, now) {
+                assert!(deadline <= now);
+                fired.push(deadline);
+            }
+            assert!(deadlines[fired.len()..].iter().all(|&d| d > now));
+        }
+        assert_eq!(fired, deadlines);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn cancel_and_reset() {
+        let mut wheel = TimerWheel::with_clock(ManualClock::new(0));
+        let a = wheel.insert('a', 10);
+        let b = wheel.insert('b', 300);
+        let c = wheel.insert('c', 70_000);
+        assert_eq!(wheel.deadline(b), Some(300));
+        assert_eq!(wheel.cancel(a), Some('a'));
+        assert_eq!(wheel.cancel(a), None);
+        assert!(!wheel.reset(a, 5));
+
+        assert!(wheel.reset(c, 20));
+        assert!(wheel.reset(b, 100_000));
+        assert_eq!(wheel.deadline(c), Some(20));
+        assert_eq!(drain(&mut wheel, 50_000), vec!['c']);
+        assert_eq!(wheel.deadline(c), None);
+
+        // The slot of the cancelled handle is reused, but the old handle stays dead.
+        let d = wheel.insert('d', 60_000);
+        assert_ne!(a, d);
+        assert_eq!(wheel.cancel(a), None);
+        assert!(wheel.reset(d, 40_000));
+        assert_eq!(drain(&mut wheel, 50_000), vec!['d']);
+        assert!(wheel.reset(b, 1));
+        assert_eq!(wheel.cancel(b), Some('b'));
+        assert!(wheel.is_empty());
+        assert_eq!(drain(&mut wheel, u64::MAX / 2), vec![]);
+    }
+
+    #[test]
+    fn far_deadlines() {
+        let mut wheel = TimerWheel::with_clock(ManualClock::new(5));
+        wheel.insert("far", 1 << 50);
+        wheel.insert("farther", (1 << 50) + 1);
+        wheel.insert("near", 1 << 20);
+        assert_eq!(drain(&mut wheel, 1 << 40), vec!["near"]);
+        assert_eq!(drain(&mut wheel, (1 << 50) - 1), Vec::<&str>::new());
+        assert_eq!(drain(&mut wheel, 1 << 50), vec!["far"]);
+        assert_eq!(drain(&mut wheel, u64::MAX), vec!["farther"]);
+    }
+
+    #[test]
+    fn late_inserts_fire_in_deadline_order() {
+        let mut wheel = TimerWheel::with_clock(ManualClock::new(0));
+        wheel.insert('a', 64);
+        wheel.insert('b', 64);
+        wheel.insert('d', 100);
+        assert_eq!(wheel.poll_expired(100), Some('a'));
+        wheel.insert('c', 10);
+        wheel.insert('e', 100);
+        assert_eq!(drain(&mut wheel, 100), vec!['c', 'b', 'd', 'e']);
+    }
+
+    #[test]
+    fn same_deadline_keeps_insertion_order() {
+        let mut wheel = TimerWheel::with_clock(ManualClock::new(0));
+        for i in 0..10 {
+            wheel.insert(i, 1000);
+        }
+        assert_eq!(drain(&mut wheel, 999), vec![]);
+        assert_eq!(drain(&mut wheel, 1000), (0..10).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,136 @@
+use std::ops::RangeInclusive;
+
+use crate::binary_search::Probes;
+
+const INV_PHI: f64 = 0.618_033_988_749_895;
+
+pub fn ternary_search<T, F>(domain: RangeInclusive<i64>, f: F) -> i64
+where
+    T: PartialOrd,
+    F: FnMut(i64) -> T,
+{
+    ternary_search_probed(domain, f, &mut Probes::new())
+}
+
+pub fn ternary_search_probed<T, F>(
+    domain: RangeInclusive<i64>,
+    mut f: F,
+    probes: &mut Probes,
+) -> i64
+where
+    T: PartialOrd,
+    F: FnMut(i64) -> T,
+{
+    let (mut lo, mut hi) = domain.into_inner();
+    assert!(lo <= hi, "empty search domain");
+    let mut eval = |x: i64| {
+        probes.record();
+        f(x)
+    };
+
+    while hi as i128 - lo as i128 > 2 {
+        let third = ((hi as i128 - lo as i128) / 3) as i64;
+        let (m1, m2) = (lo + third, hi - third);
+        if eval(m1) < eval(m2) {
+            lo = m1 + 1;
+        } else {
+            hi = m2 - 1;
+        }
+    }
+
+    let mut best = lo;
+    let mut best_value = eval(lo);
+    for x in (lo..=hi).skip(1) {
+        let value = eval(x);
+        if value > best_value {
+            best = x;
+            best_value = value;
+        }
+    }
+    best
+}
+
+pub fn golden_section_search<F>(lo: f64, hi: f64, tolerance: f64, f: F) -> f64
+where
+    F: FnMut(f64) -> f64,
+{
+    golden_section_search_probed(lo, hi, tolerance, f, &mut Probes::new())
+}
+
+pub fn golden_section_search_probed<F>(
+    mut lo: f64,
+    mut hi: f64,
+    tolerance: f64,
+    mut f: F,
+    probes: &mut Probes,
+) -> f64
+where
+    F: FnMut(f64) -> f64,
+{
+    assert!(lo <= hi, "empty search domain");
+    assert!(tolerance > 0.0, "tolerance must be positive");
+    let mut eval = |x: f64| {
+        probes.record();
+        f(x)
+    };
+
+    let mut x1 = hi - INV_PHI * (hi - lo);
+    let mut x2 = lo + INV_PHI * (hi - lo);
+    let (mut f1, mut f2) = (eval(x1), eval(x2));
+    while hi - lo > tolerance {
+        if f1 < f2 {
+            lo = x1;
+            x1 = x2;
+            f1 = f2;
+            x2 = lo + INV_PHI * (hi - lo);
+            f2 = eval(x2);
+        } else {
+            hi = x2;
+            x2 = x1;
+            f2 = f1;
+            x1 = hi - INV_PHI * (hi - lo);
+            f1 = eval(x1);
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        for peak in -50..50 {
+            assert_eq!(ternary_search(-50..=49, |x| -(x - peak).abs()), peak);
+        }
+        assert_eq!(ternary_search(7..=7, |x| x), 7);
+        assert_eq!(ternary_search(0..=1, |x| x), 1);
+        assert_eq!(ternary_search(i64::MIN..=i64::MAX, |x| x), i64::MAX);
+        assert_eq!(ternary_search(i64::MAX..=i64::MAX, |x| x), i64::MAX);
+        assert_eq!(
+            ternary_search(i64::MAX - 1..=i64::MAX, |x| -x),
+            i64::MAX - 1
+        );
+
+        let x = golden_section_search(0.0, 10.0, 1e-9, |x| -(x - 3.7) * (x - 3.7));
+        assert!((x - 3.7).abs() < 1e-6);
+        let x = golden_section_search(0.0, std::f64::consts::PI, 1e-9, f64::sin);
+        assert!((x - std::f64::consts::FRAC_PI_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn counts_probes() {
+        let mut probes = Probes::new();
+        assert_eq!(
+            ternary_search_probed(0..=1_000_000, |x| -(x - 1234).abs(), &mut probes),
+            1234
+        );
+        assert!(probes.count() <= 2 * 36 + 3);
+
+        let mut probes = Probes::new();
+        golden_section_search_probed(0.0, 1.0, 1e-6, |x| x * (1.0 - x), &mut probes);
+        // One probe per iteration after the first two, each shrinking the bracket by 1/phi.
+        assert_eq!(probes.count(), 2 + 29);
+    }
+}